@@ -1,184 +1,2643 @@
-use cosmwasm_std::{Addr, Coin, DepsMut, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    Addr, BankMsg, Coin, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, Response, StdError,
+    StdResult, Storage, SubMsg, Timestamp, to_binary, Uint128, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 use cw_storage_plus::Item;
+use semver::Version;
 use serde::{Deserialize, Serialize};
 
 use cw2::{ContractVersion, get_contract_version, set_contract_version};
 use crate::error::ContractError;
 
-use crate::msg::InstantiateMsg;
-use crate::state::{PARENT_DONATION, ParentDonation, STATE, State};
+use crate::msg::{
+    CampaignKind, DonatingPeriodKind, DonationHookMsg, ExecMsg, GovernanceConfig, HookErrorPolicy, InstantiateMsg,
+    InsufficientDonationPolicy, MigrateMsg, MilestoneRule, Parent, ParentResp, ProposalStatus, QueryMsg, ReceiveMsg,
+    RewardTokenInit,
+};
+use crate::state::{
+    ACCEPTED_TOKENS, BADGE_COLLECTION, BADGES, Campaign, CAMPAIGNS, COUNTER_HISTORY, DONATION_TOTALS, DonorRecord,
+    FORWARDS_IN_FLIGHT, GOVERNANCE, GUARDIAN, HOOKS, HOOKS_IN_FLIGHT, MAX_PARENT_DEPTH, MILESTONES, ParentDonation,
+    ParentForward, PARENTS, PAUSED, PENDING_FORWARDS, Proposal, REWARD_CONFIG, RewardConfig, REWARDS_MINTED,
+    REWARD_TOKEN, STAKING, State, STATE, TOP_DONORS, TOTAL_REWARDS_MINTED, WITHDRAW_TIMELOCK,
+};
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const DEFAULT_MAX_PARENT_DEPTH: u32 = 5;
+pub const MAX_TOP_DONORS: u32 = 20;
 
 
-pub fn instantiate(deps: DepsMut, msg: InstantiateMsg, info: MessageInfo) -> StdResult<Response> {
+pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg, info: MessageInfo) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let counter = msg.counter.unwrap_or_else(|| 0);
-    let minimal_donation = msg.minimal_donation;
+    let minimal_donations = validate_minimal_donations(msg.minimal_donations)?;
     let owner = info.sender;
-    let donating_parent = msg.parent.as_ref().map(|p| p.donating_period);
+    let insufficient_donation = msg.insufficient_donation.unwrap_or_default();
     STATE.save(
         deps.storage,
         &State {
             counter,
-            minimal_donation,
-            owner,
-            donating_parent,
+            minimal_donations,
+            owner: Some(owner),
+            insufficient_donation,
+        },
+    )?;
+    COUNTER_HISTORY.save(deps.storage, &counter, env.block.height)?;
+
+    if let Some(max_depth) = msg.max_parent_depth {
+        MAX_PARENT_DEPTH.save(deps.storage, &max_depth)?;
+    }
+
+    for parent in validate_parents(deps.as_ref(), &env, msg.parents)? {
+        PARENTS.save(deps.storage, &parent.address, &parent)?;
+    }
+
+    for token in msg.accepted_tokens {
+        let address = deps.api.addr_validate(&token.address)?;
+        if ACCEPTED_TOKENS.has(deps.storage, &address) {
+            return Err(ContractError::DuplicatedToken { token: address.into() });
+        }
+        ACCEPTED_TOKENS.save(deps.storage, &address, &token.minimal_donation)?;
+    }
+
+    if let Some(timelock) = msg.withdraw_timelock {
+        WITHDRAW_TIMELOCK.save(deps.storage, &timelock)?;
+    }
+
+    if let Some(guardian) = msg.guardian {
+        let guardian = deps.api.addr_validate(&guardian)?;
+        GUARDIAN.save(deps.storage, &guardian)?;
+    }
+
+    if let Some(governance) = msg.governance {
+        let valid_ratio = |ratio: Decimal| !ratio.is_zero() && ratio <= Decimal::one();
+        if !valid_ratio(governance.quorum) || !valid_ratio(governance.threshold) || governance.voting_period == 0 {
+            return Err(ContractError::InvalidGovernanceConfig);
+        }
+        GOVERNANCE.save(deps.storage, &governance)?;
+    }
+
+    if let Some(staking) = msg.staking {
+        if staking.max_part.is_zero() || staking.max_part > Decimal::one() {
+            return Err(ContractError::InvalidStakingConfig);
+        }
+        STAKING.save(deps.storage, &staking)?;
+    }
+
+    let mut resp = Response::new();
+    if let Some(reward_token) = msg.reward_token {
+        resp = resp.add_submessage(instantiate_reward_token(deps, &env, reward_token)?);
+    }
+
+    Ok(resp)
+}
+
+/// The instantiate message of cw20-base, which is not a dependency of the contract
+#[derive(Serialize)]
+struct Cw20InstantiateMsg {
+    name: String,
+    symbol: String,
+    decimals: u8,
+    initial_balances: Vec<Cw20Coin>,
+    mint: Option<MinterResponse>,
+}
+
+/// The token address is stored by the `reply` entry point once the instantiation succeeds
+fn instantiate_reward_token(deps: DepsMut, env: &Env, init: RewardTokenInit) -> Result<SubMsg, ContractError> {
+    for (index, rate) in init.rates.iter().enumerate() {
+        if init.rates[..index].iter().any(|r| r.denom == rate.denom) {
+            return Err(ContractError::DuplicatedDenom { denom: rate.denom.clone() });
+        }
+    }
+
+    REWARD_CONFIG.save(
+        deps.storage,
+        &RewardConfig {
+            rates: init.rates,
+            donor_cap: init.donor_cap,
+            total_cap: init.total_cap,
         },
     )?;
 
-    let parent = msg.parent;
-    if let Some(parent) = parent {
-        PARENT_DONATION.save(
+    let msg = WasmMsg::Instantiate {
+        admin: None,
+        code_id: init.code_id,
+        msg: to_binary(&Cw20InstantiateMsg {
+            name: init.name,
+            symbol: init.symbol.clone(),
+            decimals: init.decimals,
+            initial_balances: vec![],
+            mint: Some(MinterResponse {
+                minter: env.contract.address.to_string(),
+                cap: init.total_cap,
+            }),
+        })?,
+        funds: vec![],
+        label: format!("{} reward token", init.symbol),
+    };
+
+    Ok(SubMsg::reply_on_success(msg, REWARD_TOKEN_REPLY << 32))
+}
+
+fn validate_minimal_donations(minimal_donations: Vec<Coin>) -> Result<Vec<Coin>, ContractError> {
+    for (index, coin) in minimal_donations.iter().enumerate() {
+        if minimal_donations[..index].iter().any(|c| c.denom == coin.denom) {
+            return Err(ContractError::DuplicatedDenom { denom: coin.denom.clone() });
+        }
+    }
+
+    Ok(minimal_donations)
+}
+
+fn validate_parents(deps: Deps, env: &Env, parents: Vec<Parent>) -> Result<Vec<ParentDonation>, ContractError> {
+    let counter = STATE.load(deps.storage)?.counter;
+    let mut total_part = Decimal::zero();
+    let mut validated: Vec<ParentDonation> = Vec::with_capacity(parents.len());
+
+    for parent in parents {
+        let address = deps.api.addr_validate(&parent.addr)?;
+        if validated.iter().any(|p| p.address == address) {
+            return Err(ContractError::DuplicatedParent { address: address.into() });
+        }
+        if parent.donating_period == 0 {
+            return Err(ContractError::InvalidDonatingPeriod { address: address.into() });
+        }
+
+        total_part = total_part
+            .checked_add(parent.part)
+            .map_err(|_| ContractError::InvalidParentParts { total: Decimal::MAX })?;
+        validated.push(ParentDonation {
+            address,
+            donating_parent_period: parent.donating_period,
+            part: parent.part,
+            donations_left: parent.donating_period,
+            depth: 0,
+            last_forward: period_clock(&parent.period_kind, env).unwrap_or_default(),
+            period_kind: parent.period_kind,
+            unforwarded: vec![],
+            unforwarded_tokens: vec![],
+            forwarded_at: counter,
+        });
+    }
+
+    if total_part > Decimal::one() {
+        return Err(ContractError::InvalidParentParts { total: total_part });
+    }
+
+    let max_depth = MAX_PARENT_DEPTH.may_load(deps.storage)?.unwrap_or(DEFAULT_MAX_PARENT_DEPTH);
+    for parent in &mut validated {
+        parent.depth = parent_chain_depth(deps, env, &parent.address, max_depth)?;
+    }
+
+    Ok(validated)
+}
+
+/// The current time in seconds or block height the period of a parent is measured against,
+/// `None` when it counts donations
+fn period_clock(kind: &DonatingPeriodKind, env: &Env) -> Option<u64> {
+    match kind {
+        DonatingPeriodKind::Donations => None,
+        DonatingPeriodKind::Seconds => Some(env.block.time.seconds()),
+        DonatingPeriodKind::Blocks => Some(env.block.height),
+    }
+}
+
+/// The part of the donations since the previous forward the parent is owed
+fn parent_share(parent: &ParentDonation) -> (Vec<Coin>, Vec<Cw20CoinVerified>) {
+    let funds = parent
+        .unforwarded
+        .iter()
+        .map(|coin| Coin {
+            denom: coin.denom.clone(),
+            amount: coin.amount * parent.part,
+        })
+        .collect();
+    let tokens = parent
+        .unforwarded_tokens
+        .iter()
+        .map(|token| Cw20CoinVerified {
+            address: token.address.clone(),
+            amount: token.amount * parent.part,
+        })
+        .collect();
+
+    (funds, tokens)
+}
+
+/// Takes the donations the `forwarded` amount was the part of out of the unforwarded pool, so
+/// whatever could not be forwarded, rounding leftovers included, is owed at the next forward
+fn unpooled(unforwarded: Uint128, forwarded: Uint128, part: Decimal) -> Uint128 {
+    if part.is_zero() {
+        return Uint128::zero();
+    }
+    unforwarded.saturating_sub(forwarded.multiply_ratio(Decimal::one().atomics(), part.atomics()))
+}
+
+fn token_coins(tokens: &[Cw20CoinVerified]) -> Vec<Coin> {
+    tokens
+        .iter()
+        .map(|token| Coin {
+            denom: token.address.to_string(),
+            amount: token.amount,
+        })
+        .collect()
+}
+
+/// Walks the chain above `parent` with `QueryMsg::Parent {}`, so forwarded donations can
+/// neither come back to this contract nor go through more than `max_depth` contracts. Only the
+/// chain above this contract is checked: the chains of its children get deeper with it, and
+/// are only checked again once they update their own parents.
+fn parent_chain_depth(deps: Deps, env: &Env, parent: &Addr, max_depth: u32) -> Result<u32, ContractError> {
+    let mut level = vec![parent.clone()];
+    let mut depth = 0;
+
+    while !level.is_empty() {
+        depth += 1;
+        if depth > max_depth {
+            return Err(ContractError::ParentChainTooDeep { max_depth });
+        }
+
+        let mut next: Vec<Addr> = vec![];
+        for address in level {
+            if address == env.contract.address {
+                return Err(ContractError::ParentCycle { address: parent.into() });
+            }
+
+            let resp: ParentResp = deps.querier.query_wasm_smart(&address, &QueryMsg::Parent {})?;
+            // Chains the parent already reports as too deep are rejected without walking them
+            if depth + resp.depth > max_depth {
+                return Err(ContractError::ParentChainTooDeep { max_depth });
+            }
+            for grandparent in resp.parents {
+                if !next.contains(&grandparent) {
+                    next.push(grandparent);
+                }
+            }
+        }
+        level = next;
+    }
+
+    Ok(depth)
+}
+
+/// Applies the provided config changes, returning a `config_updated` event describing them.
+/// Replacing the parents restarts every parent countdown from its full donating period, while
+/// the parents which stay keep their unforwarded donations.
+fn update_config(
+    deps: DepsMut,
+    env: &Env,
+    minimal_donations: Option<Vec<Coin>>,
+    insufficient_donation: Option<InsufficientDonationPolicy>,
+    parents: Option<Vec<Parent>>,
+) -> Result<Event, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let mut event = Event::new("config_updated");
+
+    if let Some(minimal_donations) = minimal_donations {
+        let minimal_donations = validate_minimal_donations(minimal_donations)?;
+        let thresholds: Vec<_> = minimal_donations.iter().map(Coin::to_string).collect();
+        event = event.add_attribute("minimal_donations", thresholds.join(","));
+        state.minimal_donations = minimal_donations;
+    }
+
+    if let Some(insufficient_donation) = insufficient_donation {
+        event = event.add_attribute("insufficient_donation", insufficient_donation.to_string());
+        state.insufficient_donation = insufficient_donation;
+    }
+
+    if let Some(parents) = parents {
+        let mut parents = validate_parents(deps.as_ref(), env, parents)?;
+
+        let old_parents = PARENTS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (address, old_parent) in old_parents {
+            if let Some(parent) = parents.iter_mut().find(|p| p.address == address) {
+                parent.unforwarded = old_parent.unforwarded;
+                parent.unforwarded_tokens = old_parent.unforwarded_tokens;
+                parent.forwarded_at = old_parent.forwarded_at;
+            }
+            PARENTS.remove(deps.storage, &address);
+        }
+
+        let addresses: Vec<_> = parents.iter().map(|p| p.address.as_str()).collect();
+        event = event.add_attribute("parents", addresses.join(","));
+
+        for parent in &parents {
+            PARENTS.save(deps.storage, &parent.address, parent)?;
+        }
+    }
+
+    STATE.save(deps.storage, &state)?;
+
+    Ok(event)
+}
+
+fn ensure_owner(state: &State, sender: &Addr) -> Result<(), ContractError> {
+    match &state.owner {
+        Some(owner) if owner == sender => Ok(()),
+        Some(owner) => Err(ContractError::Unauthorized { owner: owner.to_string() }),
+        None => Err(ContractError::OwnershipRenounced),
+    }
+}
+
+/// The guardian is allowed next to the owner only on the emergency paths
+fn ensure_owner_or_guardian(storage: &dyn Storage, state: &State, sender: &Addr) -> Result<(), ContractError> {
+    if GUARDIAN.may_load(storage)?.as_ref() == Some(sender) {
+        return Ok(());
+    }
+
+    ensure_owner(state, sender)
+}
+
+pub fn ensure_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if PAUSED.may_load(storage)?.unwrap_or_default() {
+        return Err(ContractError::Paused);
+    }
+
+    Ok(())
+}
+
+/// Instant withdrawals are only allowed when no timelock is configured
+fn ensure_no_timelock(storage: &dyn Storage) -> Result<(), ContractError> {
+    match WITHDRAW_TIMELOCK.may_load(storage)? {
+        Some(timelock) => Err(ContractError::WithdrawalTimelocked { timelock }),
+        None => Ok(()),
+    }
+}
+
+fn ensure_not_governed(storage: &dyn Storage) -> Result<(), ContractError> {
+    match GOVERNANCE.may_load(storage)? {
+        Some(_) => Err(ContractError::WithdrawalsGoverned),
+        None => Ok(()),
+    }
+}
+
+/// `Open` proposals pass before they expire only once the votes left cannot change the outcome
+fn proposal_status(proposal: &Proposal, config: &GovernanceConfig, now: Timestamp) -> ProposalStatus {
+    if proposal.status != ProposalStatus::Open {
+        return proposal.status.clone();
+    }
+
+    let reaches = |votes: Uint128, weight: Uint128, ratio: Decimal| {
+        !weight.is_zero() && Decimal::from_ratio(votes, weight) >= ratio
+    };
+
+    let tally = &proposal.tally;
+    let quorum = reaches(tally.yes + tally.no + tally.abstain, proposal.total_weight, config.quorum);
+    let expired = now >= proposal.expires;
+    let opinions = if expired {
+        tally.yes + tally.no
+    } else {
+        proposal.total_weight.saturating_sub(tally.abstain)
+    };
+
+    if quorum && reaches(tally.yes, opinions, config.threshold) {
+        ProposalStatus::Passed
+    } else if expired {
+        ProposalStatus::Rejected
+    } else {
+        ProposalStatus::Open
+    }
+}
+
+/// Caps the balances to the `funds` limits, with cw20 limits given by the token address as denom
+fn limit_balances(balance: &mut [Coin], tokens: &mut [Cw20CoinVerified], funds: &[Coin]) {
+    let limit = |denom: &str| funds
+        .iter()
+        .find(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .unwrap_or_default();
+
+    for coin in balance {
+        coin.amount = std::cmp::min(coin.amount, limit(&coin.denom));
+    }
+    for token in tokens {
+        token.amount = std::cmp::min(token.amount, limit(token.address.as_str()));
+    }
+}
+
+/// Part of the raised funds which has to stay in the contract to pay the campaign refunds
+fn campaign_reserve(campaign: &Campaign, now: Timestamp) -> Uint128 {
+    match campaign.kind {
+        CampaignKind::KeepItAll => Uint128::zero(),
+        CampaignKind::AllOrNothing if now >= campaign.end && campaign.raised >= campaign.target.amount => {
+            Uint128::zero()
+        }
+        CampaignKind::AllOrNothing => campaign.raised - campaign.refunded,
+    }
+}
+
+/// Native balance of the contract without the funds reserved for campaign refunds and the
+/// ones owed to parents, either by failed forwards or as their part of unforwarded donations
+fn available_balance(deps: Deps, env: &Env) -> StdResult<Vec<Coin>> {
+    available_balance_except(deps, env, &[])
+}
+
+/// Same as `available_balance`, but with the parts owed to the `sharing` parents left in
+fn available_balance_except(deps: Deps, env: &Env, sharing: &[Addr]) -> StdResult<Vec<Coin>> {
+    let mut balance = deps.querier.query_all_balances(&env.contract.address)?;
+
+    for item in CAMPAIGNS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, campaign) = item?;
+        let reserve = campaign_reserve(&campaign, env.block.time);
+        if let Some(coin) = balance.iter_mut().find(|c| c.denom == campaign.target.denom) {
+            coin.amount = coin.amount.saturating_sub(reserve);
+        }
+    }
+
+    for item in PENDING_FORWARDS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, pending) = item?;
+        sub_coins(&mut balance, &pending.funds);
+    }
+    for item in PARENTS.range(deps.storage, None, None, Order::Ascending) {
+        let (address, parent) = item?;
+        if !sharing.contains(&address) {
+            sub_coins(&mut balance, &parent_share(&parent).0);
+        }
+    }
+
+    Ok(balance)
+}
+
+/// Balances of the accepted cw20 tokens without the ones owed to parents
+fn available_tokens(deps: Deps, env: &Env) -> StdResult<Vec<Cw20CoinVerified>> {
+    available_tokens_except(deps, env, &[])
+}
+
+/// Same as `available_tokens`, but with the parts owed to the `sharing` parents left in
+fn available_tokens_except(deps: Deps, env: &Env, sharing: &[Addr]) -> StdResult<Vec<Cw20CoinVerified>> {
+    let mut tokens = token_balances(deps, &env.contract.address)?;
+
+    for item in PENDING_FORWARDS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, pending) = item?;
+        sub_tokens(&mut tokens, &pending.tokens);
+    }
+    for item in PARENTS.range(deps.storage, None, None, Order::Ascending) {
+        let (address, parent) = item?;
+        if !sharing.contains(&address) {
+            sub_tokens(&mut tokens, &parent_share(&parent).1);
+        }
+    }
+    tokens.retain(|token| !token.amount.is_zero());
+
+    Ok(tokens)
+}
+
+/// Undelegates the bonded staking denom a withdrawal of `requested` falls short of once `paid`
+/// out, everything bonded when nothing is requested. The funds are withdrawable once unbonded.
+fn undelegate_shortfall(
+    deps: Deps,
+    env: &Env,
+    requested: Option<&[Coin]>,
+    paid: &[Coin],
+) -> StdResult<Vec<StakingMsg>> {
+    let staking = match STAKING.may_load(deps.storage)? {
+        Some(staking) => staking,
+        None => return Ok(vec![]),
+    };
+
+    let amount_of = |coins: &[Coin]| {
+        coins
+            .iter()
+            .find(|coin| coin.denom == staking.denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_default()
+    };
+    let mut shortfall = match requested {
+        Some(requested) => amount_of(requested).saturating_sub(amount_of(paid)),
+        None => Uint128::MAX,
+    };
+
+    let mut msgs = vec![];
+    for delegation in deps.querier.query_all_delegations(&env.contract.address)? {
+        if shortfall.is_zero() {
+            break;
+        }
+        if delegation.amount.denom != staking.denom {
+            continue;
+        }
+
+        let amount = std::cmp::min(shortfall, delegation.amount.amount);
+        if amount.is_zero() {
+            continue;
+        }
+        shortfall -= amount;
+        msgs.push(StakingMsg::Undelegate {
+            validator: delegation.validator,
+            amount: Coin::new(amount.u128(), &staking.denom),
+        });
+    }
+
+    Ok(msgs)
+}
+
+/// Funds delegated by the contract, summed over the validators
+fn bonded_balance(deps: Deps, env: &Env) -> StdResult<Vec<Coin>> {
+    let mut bonded = vec![];
+    for delegation in deps.querier.query_all_delegations(&env.contract.address)? {
+        if !delegation.amount.amount.is_zero() {
+            add_coins(&mut bonded, &[delegation.amount]);
+        }
+    }
+
+    Ok(bonded)
+}
+
+fn add_coins(total: &mut Vec<Coin>, coins: &[Coin]) {
+    for coin in coins {
+        match total.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(existing) => existing.amount += coin.amount,
+            None => total.push(coin.clone()),
+        }
+    }
+}
+
+fn sub_coins(total: &mut [Coin], coins: &[Coin]) {
+    for coin in coins {
+        if let Some(existing) = total.iter_mut().find(|c| c.denom == coin.denom) {
+            existing.amount = existing.amount.saturating_sub(coin.amount);
+        }
+    }
+}
+
+/// Reply ids carry the kind of submessage in the upper 32 bits and its index within the
+/// dispatching transaction in the lower ones
+pub const PARENT_FORWARD_REPLY: u64 = 1;
+pub const DONATION_HOOK_REPLY: u64 = 2;
+pub const REWARD_TOKEN_REPLY: u64 = 3;
+
+pub fn reply_kind(id: u64) -> u64 {
+    id >> 32
+}
+
+fn add_tokens(total: &mut Vec<Cw20CoinVerified>, tokens: &[Cw20CoinVerified]) {
+    for token in tokens {
+        match total.iter_mut().find(|t| t.address == token.address) {
+            Some(existing) => existing.amount += token.amount,
+            None => total.push(token.clone()),
+        }
+    }
+}
+
+fn sub_tokens(total: &mut [Cw20CoinVerified], tokens: &[Cw20CoinVerified]) {
+    for token in tokens {
+        if let Some(existing) = total.iter_mut().find(|t| t.address == token.address) {
+            existing.amount = existing.amount.saturating_sub(token.amount);
+        }
+    }
+}
+
+/// Balances of all the accepted cw20 tokens held by the contract, skipping empty ones
+fn token_balances(deps: Deps, contract: &Addr) -> StdResult<Vec<Cw20CoinVerified>> {
+    let tokens = ACCEPTED_TOKENS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut balances = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let resp: BalanceResponse = deps.querier.query_wasm_smart(
+            token.to_string(),
+            &Cw20QueryMsg::Balance {
+                address: contract.to_string(),
+            },
+        )?;
+
+        if !resp.balance.is_zero() {
+            balances.push(Cw20CoinVerified {
+                address: token,
+                amount: resp.balance,
+            });
+        }
+    }
+
+    Ok(balances)
+}
+
+/// Messages sending the native funds and cw20 tokens to the recipient, skipping empty amounts
+fn payout(recipient: &str, funds: Vec<Coin>, tokens: Vec<Cw20CoinVerified>) -> StdResult<Vec<CosmosMsg>> {
+    let funds: Vec<_> = funds.into_iter().filter(|coin| !coin.amount.is_zero()).collect();
+
+    let mut msgs = vec![];
+    if !funds.is_empty() {
+        msgs.push(
+            BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: funds,
+            }
+            .into(),
+        );
+    }
+
+    for token in tokens {
+        if token.amount.is_zero() {
+            continue;
+        }
+
+        msgs.push(
+            WasmMsg::Execute {
+                contract_addr: token.address.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: token.amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    Ok(msgs)
+}
+
+/// Builds the forwards of native funds and every cw20 token to a parent, skipping empty ones.
+/// Each of them is recorded as pending by the `reply` entry point if the parent rejects it,
+/// instead of reverting the whole transaction
+fn forward_to_parent(
+    storage: &mut dyn Storage,
+    next_index: &mut u64,
+    parent: &Addr,
+    funds: Vec<Coin>,
+    tokens: Vec<Cw20CoinVerified>,
+) -> StdResult<Vec<SubMsg>> {
+    let mut msgs = vec![];
+
+    if !funds.is_empty() {
+        let msg = WasmMsg::Execute {
+            contract_addr: parent.to_string(),
+            msg: to_binary(&ExecMsg::Donate {})?,
+            funds: funds.clone(),
+        };
+        let forward = ParentForward {
+            parent: parent.clone(),
+            funds,
+            tokens: vec![],
+        };
+        msgs.push(forward_in_flight(storage, next_index, forward, msg)?);
+    }
+
+    for token in tokens {
+        let msg = WasmMsg::Execute {
+            contract_addr: token.address.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: parent.to_string(),
+                amount: token.amount,
+                msg: to_binary(&ReceiveMsg::Donate {})?,
+            })?,
+            funds: vec![],
+        };
+        let forward = ParentForward {
+            parent: parent.clone(),
+            funds: vec![],
+            tokens: vec![token],
+        };
+        msgs.push(forward_in_flight(storage, next_index, forward, msg)?);
+    }
+
+    Ok(msgs)
+}
+
+fn forward_in_flight(
+    storage: &mut dyn Storage,
+    next_index: &mut u64,
+    forward: ParentForward,
+    msg: WasmMsg,
+) -> StdResult<SubMsg> {
+    let id = (PARENT_FORWARD_REPLY << 32) | *next_index;
+    *next_index += 1;
+
+    FORWARDS_IN_FLIGHT.save(storage, id, &forward)?;
+
+    Ok(SubMsg::reply_always(msg, id))
+}
+
+/// Notifies every hook about a counted donation. Hooks which fail without reverting the
+/// donation are handled by the `reply` entry point.
+fn donation_hooks(storage: &mut dyn Storage, donor: &Addr, funds: &[Coin], counter: u64) -> StdResult<Vec<SubMsg>> {
+    let hooks = HOOKS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut msgs = Vec::with_capacity(hooks.len());
+    for (index, (hook, on_error)) in hooks.into_iter().enumerate() {
+        let msg = DonationHookMsg {
+            donor: donor.clone(),
+            funds: funds.to_vec(),
+            counter,
+        }
+        .into_wasm_msg(&hook)?;
+
+        if on_error == HookErrorPolicy::Revert {
+            msgs.push(SubMsg::new(msg));
+            continue;
+        }
+
+        let id = (DONATION_HOOK_REPLY << 32) | index as u64;
+        HOOKS_IN_FLIGHT.save(storage, id, &hook)?;
+        msgs.push(SubMsg::reply_always(msg, id));
+    }
+
+    Ok(msgs)
+}
+
+/// Mints the reward for the donation, limited by the caps
+fn mint_rewards(storage: &mut dyn Storage, donor: &Addr, donated: &[Coin]) -> StdResult<Option<CosmosMsg>> {
+    let token = match REWARD_TOKEN.may_load(storage)? {
+        Some(token) => token,
+        None => return Ok(None),
+    };
+    let config = REWARD_CONFIG.load(storage)?;
+
+    let mut reward = donated
+        .iter()
+        .filter_map(|coin| {
+            let rate = config.rates.iter().find(|r| r.denom == coin.denom)?;
+            Some(coin.amount * rate.rate)
+        })
+        .fold(Uint128::zero(), |reward, amount| reward + amount);
+
+    let minted = REWARDS_MINTED.may_load(storage, donor)?.unwrap_or_default();
+    let total = TOTAL_REWARDS_MINTED.may_load(storage)?.unwrap_or_default();
+    if let Some(cap) = config.donor_cap {
+        reward = std::cmp::min(reward, cap.saturating_sub(minted));
+    }
+    if let Some(cap) = config.total_cap {
+        reward = std::cmp::min(reward, cap.saturating_sub(total));
+    }
+
+    if reward.is_zero() {
+        return Ok(None);
+    }
+
+    REWARDS_MINTED.save(storage, donor, &(minted + reward))?;
+    TOTAL_REWARDS_MINTED.save(storage, &(total + reward))?;
+
+    let msg = WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: donor.to_string(),
+            amount: reward,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Some(msg.into()))
+}
+
+/// The `Mint` message of cw721-base collections with the default `Option<Empty>` extension
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Cw721ExecuteMsg {
+    Mint {
+        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: Option<Empty>,
+    },
+}
+
+/// Mints the badges of all the milestones the donor has reached since the last donation
+fn mint_badges(storage: &mut dyn Storage, donor: &Addr, record: &DonorRecord) -> StdResult<Vec<CosmosMsg>> {
+    let collection = match BADGE_COLLECTION.may_load(storage)? {
+        Some(collection) => collection,
+        None => return Ok(vec![]),
+    };
+
+    let mut msgs = vec![];
+    for milestone in MILESTONES.may_load(storage)?.unwrap_or_default() {
+        if BADGES.has(storage, (donor, milestone.id.as_str()))
+            || !milestone_reached(storage, donor, record, &milestone.rule)?
+        {
+            continue;
+        }
+
+        let token_id = format!("{}/{}", milestone.id, donor);
+        BADGES.save(storage, (donor, milestone.id.as_str()), &token_id)?;
+
+        msgs.push(
+            WasmMsg::Execute {
+                contract_addr: collection.to_string(),
+                msg: to_binary(&Cw721ExecuteMsg::Mint {
+                    token_id,
+                    owner: donor.to_string(),
+                    token_uri: milestone.token_uri,
+                    extension: None,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    Ok(msgs)
+}
+
+/// Re-ranks the donor by their cumulative donations in `denom`, dropping them once they have none
+fn update_top_donors(storage: &mut dyn Storage, donor: &Addr, record: &DonorRecord, denom: &str) -> StdResult<()> {
+    let total = record
+        .amounts
+        .iter()
+        .find(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .unwrap_or_default();
+
+    let mut top = TOP_DONORS.may_load(storage, denom)?.unwrap_or_default();
+    top.retain(|(address, _)| address != donor);
+    if !total.is_zero() {
+        let position = top.iter().position(|(_, amount)| *amount < total).unwrap_or(top.len());
+        top.insert(position, (donor.clone(), total));
+        top.truncate(MAX_TOP_DONORS as usize);
+    }
+
+    TOP_DONORS.save(storage, denom, &top)
+}
+
+fn milestone_reached(
+    storage: &dyn Storage,
+    donor: &Addr,
+    record: &DonorRecord,
+    rule: &MilestoneRule,
+) -> StdResult<bool> {
+    let donated = |record: &DonorRecord, denom: &str| {
+        record
+            .amounts
+            .iter()
+            .find(|coin| coin.denom == denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_default()
+    };
+
+    match rule {
+        MilestoneRule::Donations { count } => Ok(record.donations >= *count),
+        MilestoneRule::Amount { amount } => Ok(donated(record, &amount.denom) >= amount.amount),
+        MilestoneRule::TopDonors { denom, rank } => {
+            let own = donated(record, denom);
+            if own.is_zero() {
+                return Ok(false);
+            }
+
+            let ahead = TOP_DONORS
+                .may_load(storage, denom)?
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(address, amount)| address != donor && *amount > own)
+                .count();
+
+            Ok(ahead < *rank as usize)
+        }
+    }
+}
+
+pub mod query {
+    use cosmwasm_std::{Addr, Coin, Deps, Env, Order, StdResult, Timestamp};
+    use cw_storage_plus::Bound;
+
+    use crate::msg::{
+        AcceptedToken, Badge, BadgesResp, CampaignResp, CampaignsResp, CampaignStatus, ConfigResp, DonatingPeriodKind,
+        DonorResp, DonorsResp, GovernanceConfig, Hook, HooksResp, MilestonesResp, OwnershipResp, Parent, ParentProgress,
+        ParentResp, ParentsResp, ParentStatusResp, PendingWithdrawal, PendingWithdrawalsResp, ProposalResp,
+        ProposalsResp, RewardTokenResp, StakingResp, StatusResp, ValueAtResp, ValueResp, VoteInfo, VotesResp,
+    };
+    use crate::state::{
+        ACCEPTED_TOKENS, BADGE_COLLECTION, BADGES, BALLOTS, Campaign, CAMPAIGNS, COUNTER_HISTORY, DONATION_TOTALS,
+        DonorRecord, DONORS, GOVERNANCE, GUARDIAN, HOOKS, MAX_PARENT_DEPTH, MILESTONES, PARENTS, PAUSED,
+        PENDING_FORWARDS, PENDING_OWNER, PENDING_WITHDRAWALS, Proposal, PROPOSALS, REWARD_CONFIG, REWARD_TOKEN, STAKING,
+        STATE, TOTAL_REWARDS_MINTED, WITHDRAW_TIMELOCK,
+    };
+
+    use super::{bonded_balance, DEFAULT_MAX_PARENT_DEPTH, proposal_status};
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    pub fn value(deps: Deps) -> StdResult<ValueResp> {
+        let value = STATE.load(deps.storage)?.counter;
+        Ok(ValueResp { value })
+    }
+
+    pub fn value_at(deps: Deps, height: u64) -> StdResult<ValueAtResp> {
+        // Snapshots hold the values from the beginning of a block, which is the end of the previous one
+        let snapshot = height.saturating_add(1);
+        let value = COUNTER_HISTORY.may_load_at_height(deps.storage, snapshot)?;
+
+        let denoms = DONATION_TOTALS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut donations = vec![];
+        for denom in denoms {
+            if let Some(amount) = DONATION_TOTALS.may_load_at_height(deps.storage, &denom, snapshot)? {
+                donations.push(Coin { denom, amount });
+            }
+        }
+
+        Ok(ValueAtResp {
+            height,
+            value,
+            donations,
+        })
+    }
+
+    pub fn increment(value: u64) -> StdResult<ValueResp> {
+        Ok(ValueResp { value: value + 1 })
+    }
+
+    pub fn donor(deps: Deps, address: String) -> StdResult<DonorResp> {
+        let address = deps.api.addr_validate(&address)?;
+        let record = DONORS.may_load(deps.storage, &address)?.unwrap_or_default();
+        Ok(donor_resp(address, record))
+    }
+
+    pub fn donors(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<DonorsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let donors = DONORS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(address, record)| donor_resp(address, record)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(DonorsResp { donors })
+    }
+
+    pub fn ownership(deps: Deps) -> StdResult<OwnershipResp> {
+        let owner = STATE.load(deps.storage)?.owner;
+        let pending = PENDING_OWNER.may_load(deps.storage)?;
+
+        Ok(OwnershipResp {
+            owner,
+            pending_owner: pending.as_ref().map(|p| p.new_owner.clone()),
+            pending_expiry: pending.and_then(|p| p.expiry),
+        })
+    }
+
+    pub fn parents(deps: Deps) -> StdResult<ParentsResp> {
+        let parents = PARENTS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(_, parent)| Parent {
+                    addr: parent.address.into(),
+                    donating_period: parent.donating_parent_period,
+                    part: parent.part,
+                    period_kind: parent.period_kind,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ParentsResp { parents })
+    }
+
+    pub fn parent(deps: Deps) -> StdResult<ParentResp> {
+        let mut parents = vec![];
+        let mut depth = 0;
+        for item in PARENTS.range(deps.storage, None, None, Order::Ascending) {
+            let (address, parent) = item?;
+            parents.push(address);
+            depth = depth.max(parent.depth);
+        }
+
+        Ok(ParentResp { parents, depth })
+    }
+
+    pub fn config(deps: Deps) -> StdResult<ConfigResp> {
+        let state = STATE.load(deps.storage)?;
+
+        let accepted_tokens = ACCEPTED_TOKENS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(address, minimal_donation)| AcceptedToken {
+                    address: address.into(),
+                    minimal_donation,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ConfigResp {
+            owner: state.owner,
+            minimal_donations: state.minimal_donations,
+            insufficient_donation: state.insufficient_donation,
+            accepted_tokens,
+            withdraw_timelock: WITHDRAW_TIMELOCK.may_load(deps.storage)?,
+            governance: GOVERNANCE.may_load(deps.storage)?,
+            max_parent_depth: MAX_PARENT_DEPTH.may_load(deps.storage)?.unwrap_or(DEFAULT_MAX_PARENT_DEPTH),
+        })
+    }
+
+    pub fn parent_status(deps: Deps) -> StdResult<ParentStatusResp> {
+        let parents = PARENTS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (_, parent) = item?;
+                let pending = PENDING_FORWARDS
+                    .may_load(deps.storage, &parent.address)?
+                    .unwrap_or_default();
+
+                let next_forward = match parent.period_kind {
+                    DonatingPeriodKind::Donations => None,
+                    _ => Some(parent.last_forward.saturating_add(parent.donating_parent_period)),
+                };
+
+                Ok(ParentProgress {
+                    address: parent.address,
+                    donating_period: parent.donating_parent_period,
+                    period_kind: parent.period_kind,
+                    donations_left: parent.donations_left,
+                    next_forward,
+                    part: parent.part,
+                    unforwarded: parent.unforwarded,
+                    unforwarded_tokens: parent.unforwarded_tokens,
+                    pending_forward: pending.funds,
+                    pending_tokens: pending.tokens,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ParentStatusResp { parents })
+    }
+
+    pub fn pending_withdrawals(deps: Deps) -> StdResult<PendingWithdrawalsResp> {
+        let withdrawals = PENDING_WITHDRAWALS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(id, withdrawal)| PendingWithdrawal {
+                    id,
+                    recipient: withdrawal.recipient,
+                    funds: withdrawal.funds,
+                    unlock: withdrawal.unlock,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(PendingWithdrawalsResp { withdrawals })
+    }
+
+    pub fn campaign(deps: Deps, env: Env, id: u64) -> StdResult<CampaignResp> {
+        let campaign = CAMPAIGNS.load(deps.storage, id)?;
+        Ok(campaign_resp(id, campaign, env.block.time))
+    }
+
+    pub fn campaigns(deps: Deps, env: Env) -> StdResult<CampaignsResp> {
+        let campaigns = CAMPAIGNS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(id, campaign)| campaign_resp(id, campaign, env.block.time)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(CampaignsResp { campaigns })
+    }
+
+    pub fn proposal(deps: Deps, env: Env, id: u64) -> StdResult<ProposalResp> {
+        let governance = GOVERNANCE.load(deps.storage)?;
+        let proposal = PROPOSALS.load(deps.storage, id)?;
+        Ok(proposal_resp(id, proposal, &governance, env.block.time))
+    }
+
+    pub fn proposals(deps: Deps, env: Env, start_after: Option<u64>, limit: Option<u32>) -> StdResult<ProposalsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let proposals = match GOVERNANCE.may_load(deps.storage)? {
+            Some(governance) => PROPOSALS
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|item| item.map(|(id, proposal)| proposal_resp(id, proposal, &governance, env.block.time)))
+                .collect::<StdResult<_>>()?,
+            None => vec![],
+        };
+
+        Ok(ProposalsResp { proposals })
+    }
+
+    pub fn votes(deps: Deps, id: u64, start_after: Option<String>, limit: Option<u32>) -> StdResult<VotesResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let votes = BALLOTS
+            .prefix(id)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(voter, ballot)| VoteInfo {
+                    voter,
+                    vote: ballot.vote,
+                    weight: ballot.weight,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(VotesResp { votes })
+    }
+
+    pub fn hooks(deps: Deps) -> StdResult<HooksResp> {
+        let hooks = HOOKS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(address, on_error)| Hook { address, on_error }))
+            .collect::<StdResult<_>>()?;
+
+        Ok(HooksResp { hooks })
+    }
+
+    pub fn milestones(deps: Deps) -> StdResult<MilestonesResp> {
+        Ok(MilestonesResp {
+            collection: BADGE_COLLECTION.may_load(deps.storage)?,
+            milestones: MILESTONES.may_load(deps.storage)?.unwrap_or_default(),
+        })
+    }
+
+    pub fn badges(deps: Deps, donor: String) -> StdResult<BadgesResp> {
+        let donor = deps.api.addr_validate(&donor)?;
+
+        let badges = BADGES
+            .prefix(&donor)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(milestone, token_id)| Badge { milestone, token_id }))
+            .collect::<StdResult<_>>()?;
+
+        Ok(BadgesResp { badges })
+    }
+
+    pub fn reward_token(deps: Deps) -> StdResult<RewardTokenResp> {
+        let config = REWARD_CONFIG.may_load(deps.storage)?;
+
+        Ok(RewardTokenResp {
+            address: REWARD_TOKEN.may_load(deps.storage)?,
+            rates: config.as_ref().map(|c| c.rates.clone()).unwrap_or_default(),
+            donor_cap: config.as_ref().and_then(|c| c.donor_cap),
+            total_cap: config.and_then(|c| c.total_cap),
+            minted: TOTAL_REWARDS_MINTED.may_load(deps.storage)?.unwrap_or_default(),
+        })
+    }
+
+    pub fn staking(deps: Deps, env: Env) -> StdResult<StakingResp> {
+        Ok(StakingResp {
+            config: STAKING.may_load(deps.storage)?,
+            liquid: deps.querier.query_all_balances(&env.contract.address)?,
+            bonded: bonded_balance(deps, &env)?,
+        })
+    }
+
+    pub fn status(deps: Deps) -> StdResult<StatusResp> {
+        Ok(StatusResp {
+            paused: PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+            guardian: GUARDIAN.may_load(deps.storage)?,
+        })
+    }
+
+    fn campaign_resp(id: u64, campaign: Campaign, now: Timestamp) -> CampaignResp {
+        let status = if now < campaign.start {
+            CampaignStatus::Upcoming
+        } else if now < campaign.end {
+            CampaignStatus::Active
+        } else if campaign.raised >= campaign.target.amount {
+            CampaignStatus::Succeeded
+        } else {
+            CampaignStatus::Failed
+        };
+
+        CampaignResp {
+            id,
+            target: campaign.target,
+            start: campaign.start,
+            end: campaign.end,
+            kind: campaign.kind,
+            raised: campaign.raised,
+            refunded: campaign.refunded,
+            status,
+        }
+    }
+
+    fn proposal_resp(id: u64, proposal: Proposal, governance: &GovernanceConfig, now: Timestamp) -> ProposalResp {
+        let status = proposal_status(&proposal, governance, now);
+
+        ProposalResp {
+            id,
+            proposer: proposal.proposer,
+            description: proposal.description,
+            action: proposal.action,
+            start_height: proposal.start_height,
+            expires: proposal.expires,
+            total_weight: proposal.total_weight,
+            tally: proposal.tally,
+            status,
+        }
+    }
+
+    fn donor_resp(address: Addr, record: DonorRecord) -> DonorResp {
+        DonorResp {
+            address,
+            donations: record.donations,
+            amounts: record.amounts,
+            first_block: record.first_block,
+            last_block: record.last_block,
+        }
+    }
+}
+
+pub mod exec {
+    use cosmwasm_std::{
+        Addr, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, DistributionMsg, Env, from_binary, MessageInfo, Order,
+        Response, StakingMsg, StdError, StdResult, Timestamp, to_binary, Uint128, WasmMsg,
+    };
+    use cw20::{Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg};
+    use cw_utils::Expiration;
+
+    use crate::error::ContractError;
+    use crate::msg::{
+        CampaignKind, HookErrorPolicy, InsufficientDonationPolicy, Milestone, MilestoneRule, Parent, ProposalAction,
+        ProposalStatus, ReceiveMsg, Tally, VoteOption,
+    };
+    use crate::state::{
+        ACCEPTED_TOKENS, BADGE_COLLECTION, Ballot, BALLOTS, Campaign, CampaignDonation, CAMPAIGN_DONORS, CAMPAIGNS,
+        COUNTER_HISTORY, DONATION_TOTALS, DonorRecord, DONORS, DONOR_WEIGHTS, GOVERNANCE, HOOKS, MILESTONES,
+        NEXT_CAMPAIGN_ID, NEXT_PROPOSAL_ID, NEXT_WITHDRAWAL_ID, PARENTS, PAUSED, PENDING_FORWARDS, PendingOwner,
+        PENDING_OWNER, PendingWithdrawal, PENDING_WITHDRAWALS, Proposal, PROPOSALS, STAKING, STATE, WITHDRAW_TIMELOCK,
+    };
+
+    use super::{
+        add_coins, add_tokens, available_balance, available_balance_except, available_tokens, available_tokens_except,
+        bonded_balance, donation_hooks, ensure_not_governed, ensure_no_timelock, ensure_owner, ensure_owner_or_guardian,
+        forward_to_parent, limit_balances, MAX_TOP_DONORS, mint_badges, mint_rewards, parent_share, payout,
+        period_clock, proposal_status, sub_coins, token_coins, unpooled, update_top_donors, validate_parents,
+    };
+
+    pub fn update_config(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        minimal_donations: Option<Vec<Coin>>,
+        insufficient_donation: Option<InsufficientDonationPolicy>,
+        parents: Option<Vec<Parent>>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        // Otherwise the owner could redirect every donation to a parent of their own
+        if parents.is_some() && GOVERNANCE.has(deps.storage) {
+            return Err(ContractError::ParentsGoverned);
+        }
+
+        let event = super::update_config(deps, &env, minimal_donations, insufficient_donation, parents)?;
+
+        let resp = Response::new()
+            .add_event(event)
+            .add_attribute("action", "update_config")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn donate(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError>  {
+        let state = STATE.load(deps.storage)?;
+
+        if !state.minimal_donations.is_empty() {
+            let unaccepted = info
+                .funds
+                .iter()
+                .find(|coin| !state.minimal_donations.iter().any(|minimal| minimal.denom == coin.denom));
+            if let Some(coin) = unaccepted {
+                return Err(ContractError::UnacceptedDenom { denom: coin.denom.clone() });
+            }
+        }
+
+        let resp = if state.minimal_donations.is_empty()
+            || state.minimal_donations.iter().any(|minimal| {
+            minimal.amount.is_zero()
+                || info.funds.iter().any(|coin| coin.denom == minimal.denom && coin.amount >= minimal.amount)
+        })
+        {
+            // Credited first, so the funds reserved for the campaign are not forwarded to parents
+            let campaign = credit_campaign(deps.branch(), &env, &info.sender, &info.funds)?;
+            // Donations an all-or-nothing campaign may still refund are rewarded once it succeeds
+            let rewarded: Vec<_> = match &campaign {
+                Some((_, campaign)) if campaign.kind == CampaignKind::AllOrNothing => info
+                    .funds
+                    .iter()
+                    .filter(|coin| coin.denom != campaign.target.denom)
+                    .cloned()
+                    .collect(),
+                _ => info.funds.clone(),
+            };
+            let resp = count_donation(deps.branch(), &env, &info.sender, &info.funds, &rewarded)?;
+            match campaign {
+                Some((id, _)) => resp.add_attribute("campaign", id.to_string()),
+                None => resp,
+            }
+        } else {
+            let refund = (!info.funds.is_empty()).then(|| {
+                BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: info.funds.clone(),
+                }
+                .into()
+            });
+            refuse_donation(&state.insufficient_donation, state.minimal_donations, refund)?
+        };
+
+        let counter = STATE.load(deps.storage)?.counter;
+        let resp = resp
+            .add_attribute("action", "poke")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("counter", counter.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn receive(deps: DepsMut, env: Env, info: MessageInfo, msg: Cw20ReceiveMsg) -> Result<Response, ContractError> {
+        let minimal_donation = ACCEPTED_TOKENS
+            .may_load(deps.storage, &info.sender)?
+            .ok_or_else(|| ContractError::UnacceptedToken { token: info.sender.to_string() })?;
+        let donor = deps.api.addr_validate(&msg.sender)?;
+
+        match from_binary(&msg.msg)? {
+            ReceiveMsg::Donate {} => donate_tokens(deps, env, info.sender, donor, msg.amount, minimal_donation),
+        }
+    }
+
+    fn donate_tokens(
+        mut deps: DepsMut,
+        env: Env,
+        token: Addr,
+        donor: Addr,
+        amount: Uint128,
+        minimal_donation: Uint128,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+
+        let resp = if amount >= minimal_donation {
+            let donated = Coin {
+                denom: token.to_string(),
+                amount,
+            };
+            count_donation(deps.branch(), &env, &donor, &[donated.clone()], &[donated])?
+        } else {
+            let refund = WasmMsg::Execute {
+                contract_addr: token.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: donor.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            };
+            let minimal = Coin {
+                denom: token.to_string(),
+                amount: minimal_donation,
+            };
+            refuse_donation(&state.insufficient_donation, vec![minimal], Some(refund.into()))?
+        };
+
+        let counter = STATE.load(deps.storage)?.counter;
+        let resp = resp
+            .add_attribute("action", "poke")
+            .add_attribute("sender", donor.as_str())
+            .add_attribute("token", token.as_str())
+            .add_attribute("counter", counter.to_string());
+
+        Ok(resp)
+    }
+
+    /// Adds the donation in the campaign denom to the campaign running at the moment, if any
+    fn credit_campaign(
+        deps: DepsMut,
+        env: &Env,
+        donor: &Addr,
+        donated: &[Coin],
+    ) -> StdResult<Option<(u64, Campaign)>> {
+        let now = env.block.time;
+        let running = CAMPAIGNS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?
+            .into_iter()
+            .find(|(_, campaign)| campaign.start <= now && now < campaign.end);
+
+        let (id, mut campaign) = match running {
+            Some(running) => running,
+            None => return Ok(None),
+        };
+
+        let amount = donated
+            .iter()
+            .find(|coin| coin.denom == campaign.target.denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_default();
+        if amount.is_zero() {
+            return Ok(None);
+        }
+
+        campaign.raised += amount;
+        CAMPAIGNS.save(deps.storage, id, &campaign)?;
+
+        // The donation is counted right after it is credited
+        let counter = STATE.load(deps.storage)?.counter + 1;
+        CAMPAIGN_DONORS.update(deps.storage, (id, donor), |donations| -> StdResult<_> {
+            let mut donations = donations.unwrap_or_default();
+            donations.push(CampaignDonation { counter, amount });
+            Ok(donations)
+        })?;
+
+        Ok(Some((id, campaign)))
+    }
+
+    /// Increments the counter, records the donation in the donor ledger, forwards to every
+    /// parent which became due and notifies the hooks. Reward tokens are minted for `rewarded` only
+    fn count_donation(
+        deps: DepsMut,
+        env: &Env,
+        donor: &Addr,
+        donated: &[Coin],
+        rewarded: &[Coin],
+    ) -> Result<Response, ContractError> {
+        let mut resp = Response::new();
+
+        let state = STATE.update(deps.storage, |mut state| -> StdResult<_> {
+            state.counter += 1;
+            Ok(state)
+        })?;
+        COUNTER_HISTORY.save(deps.storage, &state.counter, env.block.height)?;
+
+        for coin in donated {
+            DONATION_TOTALS.update(deps.storage, &coin.denom, env.block.height, |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default() + coin.amount)
+            })?;
+        }
+
+        if let Some(governance) = GOVERNANCE.may_load(deps.storage)? {
+            if let Some(coin) = donated.iter().find(|coin| coin.denom == governance.denom) {
+                DONOR_WEIGHTS.update(deps.storage, donor, env.block.height, |weight| -> StdResult<_> {
+                    Ok(weight.unwrap_or_default() + coin.amount)
+                })?;
+            }
+        }
+
+        let record = DONORS.update(deps.storage, donor, |record| -> StdResult<_> {
+            let mut record = record.unwrap_or_else(|| DonorRecord {
+                donations: 0,
+                amounts: vec![],
+                first_block: env.block.height,
+                last_block: env.block.height,
+            });
+            record.donations += 1;
+            record.last_block = env.block.height;
+            add_coins(&mut record.amounts, donated);
+            Ok(record)
+        })?;
+        for coin in donated {
+            update_top_donors(deps.storage, donor, &record, &coin.denom)?;
+        }
+
+        let parents = PARENTS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        // Donated cw20 tokens are counted with the token address as denom
+        let mut donated_funds = vec![];
+        let mut donated_tokens = vec![];
+        for coin in donated {
+            let token = Addr::unchecked(&coin.denom);
+            if ACCEPTED_TOKENS.has(deps.storage, &token) {
+                donated_tokens.push(Cw20CoinVerified {
+                    address: token,
+                    amount: coin.amount,
+                });
+            } else {
+                donated_funds.push(coin.clone());
+            }
+        }
+
+        let mut due = vec![];
+        for (_, mut parent) in parents {
+            add_coins(&mut parent.unforwarded, &donated_funds);
+            add_tokens(&mut parent.unforwarded_tokens, &donated_tokens);
+
+            let is_due = match period_clock(&parent.period_kind, env) {
+                // The first donation after the deadline forwards the share of everything accumulated since
+                Some(now) => {
+                    let is_due = now >= parent.last_forward.saturating_add(parent.donating_parent_period);
+                    if is_due {
+                        parent.last_forward = now;
+                    }
+                    is_due
+                }
+                None => {
+                    parent.donations_left -= 1;
+                    let is_due = parent.donations_left == 0;
+                    if is_due {
+                        parent.donations_left = parent.donating_parent_period;
+                    }
+                    is_due
+                }
+            };
+            if is_due {
+                due.push(parent.clone());
+            }
+            PARENTS.save(deps.storage, &parent.address, &parent)?;
+        }
+
+        if !due.is_empty() {
+            // Neither campaign refunds nor what is owed to the other parents can be forwarded
+            let sharing: Vec<_> = due.iter().map(|parent| parent.address.clone()).collect();
+            let mut available = available_balance_except(deps.as_ref(), env, &sharing)?;
+            available.extend(token_coins(&available_tokens_except(deps.as_ref(), env, &sharing)?));
+
+            let mut next_index = 0;
+            for mut parent in due {
+                // Only the donations since the previous forward are shared, so neither funds forwarded
+                // before nor funds which were never donated are forwarded again
+                let (mut funds, mut tokens) = parent_share(&parent);
+                limit_balances(&mut funds, &mut tokens, &available);
+                funds.retain(|coin| !coin.amount.is_zero());
+                tokens.retain(|token| !token.amount.is_zero());
+
+                for coin in &mut parent.unforwarded {
+                    let sent = funds.iter().find(|c| c.denom == coin.denom).map(|c| c.amount);
+                    coin.amount = unpooled(coin.amount, sent.unwrap_or_default(), parent.part);
+                }
+                for token in &mut parent.unforwarded_tokens {
+                    let sent = tokens.iter().find(|t| t.address == token.address).map(|t| t.amount);
+                    token.amount = unpooled(token.amount, sent.unwrap_or_default(), parent.part);
+                }
+                parent.unforwarded.retain(|coin| !coin.amount.is_zero());
+                parent.unforwarded_tokens.retain(|token| !token.amount.is_zero());
+                if !funds.is_empty() || !tokens.is_empty() {
+                    parent.forwarded_at = state.counter;
+                }
+                PARENTS.save(deps.storage, &parent.address, &parent)?;
+
+                // Each forward lowers what is left for the next parents due at the same donation
+                let mut forwarded = funds.clone();
+                forwarded.extend(token_coins(&tokens));
+                if forwarded.is_empty() {
+                    continue;
+                }
+                sub_coins(&mut available, &forwarded);
+
+                let msgs = forward_to_parent(deps.storage, &mut next_index, &parent.address, funds, tokens)?;
+                let forwarded: Vec<_> = forwarded.iter().map(Coin::to_string).collect();
+
+                resp = resp
+                    .add_submessages(msgs)
+                    .add_attribute("donated_to_parent", parent.address.to_string())
+                    .add_attribute("parent_depth", parent.depth.to_string())
+                    .add_attribute("forwarded", forwarded.join(","));
+            }
+        }
+
+        let reward = mint_rewards(deps.storage, donor, rewarded)?;
+        let badges = mint_badges(deps.storage, donor, &record)?;
+        let hooks = donation_hooks(deps.storage, donor, donated, state.counter)?;
+
+        Ok(resp
+            .add_messages(reward)
+            .add_messages(badges)
+            .add_submessages(hooks)
+            .add_attribute("donation", "counted"))
+    }
+
+    fn refuse_donation(
+        policy: &InsufficientDonationPolicy,
+        minimal: Vec<Coin>,
+        refund: Option<CosmosMsg>,
+    ) -> Result<Response, ContractError> {
+        match policy {
+            InsufficientDonationPolicy::Reject => Err(ContractError::InsufficientDonation { minimal }),
+            InsufficientDonationPolicy::Refund => Ok(Response::new()
+                .add_messages(refund)
+                .add_attribute("donation", "refunded")),
+            InsufficientDonationPolicy::Keep => Ok(Response::new().add_attribute("donation", "kept")),
+        }
+    }
+
+    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_governed(deps.storage)?;
+        ensure_no_timelock(deps.storage)?;
+
+        let balance = available_balance(deps.as_ref(), &env)?;
+        let tokens = available_tokens(deps.as_ref(), &env)?;
+        let undelegations = undelegate_shortfall(deps.as_ref(), &env, None, &balance)?;
+
+        let resp = Response::new()
+            .add_messages(payout(info.sender.as_str(), balance, tokens)?)
+            .add_messages(undelegations)
+            .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn withdraw_to(deps: DepsMut, env: Env, info: MessageInfo, recipient: String, funds: Option<Vec<Coin>>) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        ensure_not_governed(deps.storage)?;
+        ensure_no_timelock(deps.storage)?;
+
+        let mut balance = available_balance(deps.as_ref(), &env)?;
+        let mut tokens = available_tokens(deps.as_ref(), &env)?;
+
+        let funds = funds.filter(|funds| !funds.is_empty());
+        if let Some(funds) = &funds {
+            limit_balances(&mut balance, &mut tokens, funds);
+        }
+        let undelegations = undelegate_shortfall(deps.as_ref(), &env, funds.as_deref(), &balance)?;
+
+        let resp = Response::new()
+            .add_messages(payout(&recipient, balance, tokens)?)
+            .add_messages(undelegations)
+            .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn withdraw_split(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        shares: Vec<(String, Decimal)>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_governed(deps.storage)?;
+        ensure_no_timelock(deps.storage)?;
+
+        if shares.is_empty() {
+            return Err(ContractError::NoWithdrawShares);
+        }
+
+        let mut recipients: Vec<(Addr, Decimal)> = Vec::with_capacity(shares.len());
+        let mut total = Decimal::zero();
+        for (address, share) in shares {
+            let address = deps.api.addr_validate(&address)?;
+            if recipients.iter().any(|(recipient, _)| *recipient == address) {
+                return Err(ContractError::DuplicatedRecipient { address: address.into_string() });
+            }
+            total = total.checked_add(share).map_err(StdError::from)?;
+            recipients.push((address, share));
+        }
+
+        // Shares which can't add up to exactly 1, like thirds, may each fall short by the
+        // smallest decimal step, and the rest is paid out as dust
+        let tolerance = Decimal::raw(recipients.len() as u128);
+        if total > Decimal::one() || Decimal::one() - total > tolerance {
+            return Err(ContractError::InvalidWithdrawShares { total });
+        }
+
+        let balance = available_balance(deps.as_ref(), &env)?;
+        let tokens = available_tokens(deps.as_ref(), &env)?;
+
+        // Every recipient gets the rounded down part of each balance, and the dust left
+        // over by the rounding goes to the first one
+        let mut payouts: Vec<(Vec<Coin>, Vec<Cw20CoinVerified>)> = recipients
+            .iter()
+            .map(|(_, share)| {
+                let funds = balance.iter().map(|c| Coin::new((c.amount * *share).u128(), &c.denom)).collect();
+                let tokens = tokens
+                    .iter()
+                    .map(|t| Cw20CoinVerified {
+                        address: t.address.clone(),
+                        amount: t.amount * *share,
+                    })
+                    .collect();
+                (funds, tokens)
+            })
+            .collect();
+
+        for (index, coin) in balance.iter().enumerate() {
+            let paid = payouts.iter().fold(Uint128::zero(), |paid, (funds, _)| paid + funds[index].amount);
+            payouts[0].0[index].amount += coin.amount - paid;
+        }
+        for (index, token) in tokens.iter().enumerate() {
+            let paid = payouts.iter().fold(Uint128::zero(), |paid, (_, tokens)| paid + tokens[index].amount);
+            payouts[0].1[index].amount += token.amount - paid;
+        }
+
+        let mut resp = Response::new()
+            .add_attribute("action", "withdraw_split")
+            .add_attribute("sender", info.sender.as_str());
+
+        for ((recipient, share), (funds, tokens)) in recipients.iter().zip(payouts) {
+            resp = resp
+                .add_messages(payout(recipient.as_str(), funds, tokens)?)
+                .add_attribute("recipient", format!("{}:{}", recipient, share));
+        }
+
+        Ok(resp)
+    }
+
+    pub fn request_withdraw(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: Option<String>,
+        funds: Option<Vec<Coin>>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+        ensure_not_governed(deps.storage)?;
+
+        let recipient = match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => info.sender.clone(),
+        };
+        let timelock = WITHDRAW_TIMELOCK.may_load(deps.storage)?.unwrap_or_default();
+        let unlock = env.block.time.plus_seconds(timelock);
+
+        let id = NEXT_WITHDRAWAL_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_WITHDRAWAL_ID.save(deps.storage, &(id + 1))?;
+        PENDING_WITHDRAWALS.save(
+            deps.storage,
+            id,
+            &PendingWithdrawal {
+                recipient: recipient.clone(),
+                funds: funds.filter(|funds| !funds.is_empty()),
+                unlock,
+            },
+        )?;
+
+        let resp = Response::new()
+            .add_attribute("action", "request_withdraw")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string())
+            .add_attribute("recipient", recipient.as_str())
+            .add_attribute("unlock", unlock.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn execute_withdraw(deps: DepsMut, env: Env, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        let withdrawal = PENDING_WITHDRAWALS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::WithdrawalNotFound { id })?;
+        if env.block.time < withdrawal.unlock {
+            return Err(ContractError::WithdrawalLocked { id, unlock: withdrawal.unlock });
+        }
+        PENDING_WITHDRAWALS.remove(deps.storage, id);
+
+        let mut balance = available_balance(deps.as_ref(), &env)?;
+        let mut tokens = available_tokens(deps.as_ref(), &env)?;
+
+        if let Some(funds) = &withdrawal.funds {
+            limit_balances(&mut balance, &mut tokens, funds);
+        }
+
+        let resp = Response::new()
+            .add_messages(payout(withdrawal.recipient.as_str(), balance, tokens)?)
+            .add_attribute("action", "execute_withdraw")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string())
+            .add_attribute("recipient", withdrawal.recipient.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn cancel_withdraw(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        if !PENDING_WITHDRAWALS.has(deps.storage, id) {
+            return Err(ContractError::WithdrawalNotFound { id });
+        }
+        PENDING_WITHDRAWALS.remove(deps.storage, id);
+
+        let resp = Response::new()
+            .add_attribute("action", "cancel_withdraw")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn pause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner_or_guardian(deps.storage, &state, &info.sender)?;
+
+        PAUSED.save(deps.storage, &true)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "pause")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        PAUSED.remove(deps.storage);
+
+        let resp = Response::new()
+            .add_attribute("action", "unpause")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn emergency_withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner_or_guardian(deps.storage, &state, &info.sender)?;
+        // The timelock queue is skipped on purpose, as its withdrawals cannot be executed while
+        // paused, but donors deciding on withdrawals cannot be bypassed by pausing
+        ensure_not_governed(deps.storage)?;
+        let owner = state.owner.ok_or(ContractError::OwnershipRenounced)?;
+
+        let balance = available_balance(deps.as_ref(), &env)?;
+        let tokens = available_tokens(deps.as_ref(), &env)?;
+
+        let resp = Response::new()
+            .add_messages(payout(owner.as_str(), balance, tokens)?)
+            .add_attribute("action", "emergency_withdraw")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("recipient", owner.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn open_campaign(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        target: Coin,
+        start: Timestamp,
+        end: Timestamp,
+        kind: CampaignKind,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        if target.amount.is_zero() {
+            return Err(ContractError::InvalidCampaignTarget);
+        }
+        if start >= end || end <= env.block.time {
+            return Err(ContractError::InvalidCampaignPeriod);
+        }
+
+        for item in CAMPAIGNS.range(deps.storage, None, None, Order::Ascending) {
+            let (id, campaign) = item?;
+            if start < campaign.end && campaign.start < end {
+                return Err(ContractError::CampaignOverlap { id });
+            }
+        }
+
+        let id = NEXT_CAMPAIGN_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_CAMPAIGN_ID.save(deps.storage, &(id + 1))?;
+        CAMPAIGNS.save(
+            deps.storage,
+            id,
+            &Campaign {
+                target: target.clone(),
+                start,
+                end,
+                kind,
+                raised: Uint128::zero(),
+                refunded: Uint128::zero(),
+            },
+        )?;
+
+        let resp = Response::new()
+            .add_attribute("action", "open_campaign")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string())
+            .add_attribute("target", target.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn claim_campaign_refund(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut campaign = CAMPAIGNS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::CampaignNotFound { id })?;
+
+        let missed = env.block.time >= campaign.end && campaign.raised < campaign.target.amount;
+        if campaign.kind != CampaignKind::AllOrNothing || !missed {
+            return Err(ContractError::CampaignNotRefundable { id });
+        }
+
+        let donations = CAMPAIGN_DONORS
+            .may_load(deps.storage, (id, &info.sender))?
+            .ok_or(ContractError::NoCampaignDonation { id })?;
+        CAMPAIGN_DONORS.remove(deps.storage, (id, &info.sender));
+        let amount: Uint128 = donations.iter().map(|donation| donation.amount).sum();
+
+        campaign.refunded += amount;
+        CAMPAIGNS.save(deps.storage, id, &campaign)?;
+
+        // Refunded donations neither count as donated nor as voting weight anymore, and the
+        // parents are not owed their part of the ones they were not forwarded yet
+        let denom = &campaign.target.denom;
+        let parents = PARENTS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (address, mut parent) in parents {
+            let unforwarded: Uint128 = donations
+                .iter()
+                .filter(|donation| donation.counter > parent.forwarded_at)
+                .map(|donation| donation.amount)
+                .sum();
+            sub_coins(&mut parent.unforwarded, &[Coin::new(unforwarded.u128(), denom)]);
+            parent.unforwarded.retain(|coin| !coin.amount.is_zero());
+            PARENTS.save(deps.storage, &address, &parent)?;
+        }
+
+        let mut record = DONORS.load(deps.storage, &info.sender)?;
+        sub_coins(&mut record.amounts, &[Coin::new(amount.u128(), denom)]);
+        record.amounts.retain(|coin| !coin.amount.is_zero());
+        DONORS.save(deps.storage, &info.sender, &record)?;
+        update_top_donors(deps.storage, &info.sender, &record, denom)?;
+        DONATION_TOTALS.update(deps.storage, denom, env.block.height, |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default().saturating_sub(amount))
+        })?;
+        if let Some(governance) = GOVERNANCE.may_load(deps.storage)? {
+            if governance.denom == *denom {
+                DONOR_WEIGHTS.update(deps.storage, &info.sender, env.block.height, |weight| -> StdResult<_> {
+                    Ok(weight.unwrap_or_default().saturating_sub(amount))
+                })?;
+            }
+        }
+
+        let resp = Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin::new(amount.u128(), campaign.target.denom)],
+            })
+            .add_attribute("action", "claim_campaign_refund")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string());
+
+        Ok(resp)
+    }
+
+    /// Mints the reward for the sender's donations to an all-or-nothing campaign which met
+    /// its target, as they could not be rewarded while a refund was still possible
+    pub fn claim_campaign_reward(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let campaign = CAMPAIGNS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::CampaignNotFound { id })?;
+
+        let succeeded = env.block.time >= campaign.end && campaign.raised >= campaign.target.amount;
+        if campaign.kind != CampaignKind::AllOrNothing || !succeeded {
+            return Err(ContractError::CampaignNotRewardable { id });
+        }
+
+        let donations = CAMPAIGN_DONORS
+            .may_load(deps.storage, (id, &info.sender))?
+            .ok_or(ContractError::NoCampaignDonation { id })?;
+        CAMPAIGN_DONORS.remove(deps.storage, (id, &info.sender));
+        let amount: Uint128 = donations.iter().map(|donation| donation.amount).sum();
+
+        let donated = Coin::new(amount.u128(), campaign.target.denom);
+        let reward = mint_rewards(deps.storage, &info.sender, &[donated])?;
+
+        let resp = Response::new()
+            .add_messages(reward)
+            .add_attribute("action", "claim_campaign_reward")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn add_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+        on_error: Option<HookErrorPolicy>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        let hook = deps.api.addr_validate(&address)?;
+        if HOOKS.has(deps.storage, &hook) {
+            return Err(ContractError::HookAlreadyRegistered { address: hook.into_string() });
+        }
+        HOOKS.save(deps.storage, &hook, &on_error.unwrap_or_default())?;
+
+        let resp = Response::new()
+            .add_attribute("action", "add_hook")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("hook", hook.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn remove_hook(deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        let hook = deps.api.addr_validate(&address)?;
+        if !HOOKS.has(deps.storage, &hook) {
+            return Err(ContractError::HookNotRegistered { address: hook.into_string() });
+        }
+        HOOKS.remove(deps.storage, &hook);
+
+        let resp = Response::new()
+            .add_attribute("action", "remove_hook")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("hook", hook.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn configure_badges(
+        deps: DepsMut,
+        info: MessageInfo,
+        collection: String,
+        milestones: Vec<Milestone>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        let collection = deps.api.addr_validate(&collection)?;
+
+        for (index, milestone) in milestones.iter().enumerate() {
+            if milestones[..index].iter().any(|m| m.id == milestone.id) {
+                return Err(ContractError::DuplicatedMilestone { id: milestone.id.clone() });
+            }
+
+            let unreachable = match &milestone.rule {
+                MilestoneRule::Donations { count } => *count == 0,
+                MilestoneRule::Amount { amount } => amount.amount.is_zero(),
+                MilestoneRule::TopDonors { rank, .. } => *rank == 0,
+            };
+            if unreachable {
+                return Err(ContractError::InvalidMilestone { id: milestone.id.clone() });
+            }
+            if let MilestoneRule::TopDonors { rank, .. } = milestone.rule {
+                if rank > MAX_TOP_DONORS {
+                    return Err(ContractError::TopDonorsRankTooHigh {
+                        id: milestone.id.clone(),
+                        max: MAX_TOP_DONORS,
+                    });
+                }
+            }
+        }
+
+        BADGE_COLLECTION.save(deps.storage, &collection)?;
+        MILESTONES.save(deps.storage, &milestones)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "configure_badges")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("collection", collection.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn propose(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        description: String,
+        recipient: String,
+        funds: Option<Vec<Coin>>,
+    ) -> Result<Response, ContractError> {
+        let recipient = deps.api.addr_validate(&recipient)?;
+        let action = ProposalAction::Withdraw {
+            recipient: recipient.clone(),
+            funds: funds.filter(|funds| !funds.is_empty()),
+        };
+
+        let resp = new_proposal(deps, &env, &info, description, action)?.add_attribute("recipient", recipient.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn propose_parents(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        description: String,
+        parents: Vec<Parent>,
+    ) -> Result<Response, ContractError> {
+        // Rejected parents would only fail once the proposal passed
+        let validated = validate_parents(deps.as_ref(), &env, parents.clone())?;
+        let addresses: Vec<_> = validated.iter().map(|p| p.address.as_str()).collect();
+        let addresses = addresses.join(",");
+
+        let action = ProposalAction::UpdateParents { parents };
+        let resp = new_proposal(deps, &env, &info, description, action)?.add_attribute("parents", addresses);
+
+        Ok(resp)
+    }
+
+    fn new_proposal(
+        deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        description: String,
+        action: ProposalAction,
+    ) -> Result<Response, ContractError> {
+        let governance = GOVERNANCE
+            .may_load(deps.storage)?
+            .ok_or(ContractError::GovernanceDisabled)?;
+
+        let weight = DONOR_WEIGHTS
+            .may_load_at_height(deps.storage, &info.sender, env.block.height)?
+            .unwrap_or_default();
+        if weight.is_zero() {
+            return Err(ContractError::NoVotingWeight);
+        }
+
+        let total_weight = DONATION_TOTALS
+            .may_load_at_height(deps.storage, &governance.denom, env.block.height)?
+            .unwrap_or_default();
+        let expires = env.block.time.plus_seconds(governance.voting_period);
+
+        let id = NEXT_PROPOSAL_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_PROPOSAL_ID.save(deps.storage, &(id + 1))?;
+        PROPOSALS.save(
             deps.storage,
-            &ParentDonation {
-                address: deps.api.addr_validate(&parent.addr)?,
-                donating_parent_period: parent.donating_period,
-                part: parent.part,
+            id,
+            &Proposal {
+                proposer: info.sender.clone(),
+                description,
+                action,
+                start_height: env.block.height,
+                expires,
+                total_weight,
+                tally: Tally::default(),
+                status: ProposalStatus::Open,
             },
         )?;
+
+        let resp = Response::new()
+            .add_attribute("action", "propose")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string())
+            .add_attribute("expires", expires.to_string());
+
+        Ok(resp)
     }
 
-    Ok(Response::new())
-}
+    pub fn vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+        vote: VoteOption,
+    ) -> Result<Response, ContractError> {
+        let mut proposal = PROPOSALS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::ProposalNotFound { id })?;
+        if proposal.status != ProposalStatus::Open || env.block.time >= proposal.expires {
+            return Err(ContractError::ProposalNotOpen { id });
+        }
 
-pub mod query {
-    use cosmwasm_std::{Deps, StdResult};
+        if BALLOTS.has(deps.storage, (id, &info.sender)) {
+            return Err(ContractError::AlreadyVoted { id });
+        }
 
-    use crate::msg::ValueResp;
-    use crate::state::STATE;
+        let weight = DONOR_WEIGHTS
+            .may_load_at_height(deps.storage, &info.sender, proposal.start_height)?
+            .unwrap_or_default();
+        if weight.is_zero() {
+            return Err(ContractError::NoVotingWeight);
+        }
 
-    pub fn value(deps: Deps) -> StdResult<ValueResp> {
-        let value = STATE.load(deps.storage)?.counter;
-        Ok(ValueResp { value })
-    }
+        match vote {
+            VoteOption::Yes => proposal.tally.yes += weight,
+            VoteOption::No => proposal.tally.no += weight,
+            VoteOption::Abstain => proposal.tally.abstain += weight,
+        }
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+        BALLOTS.save(deps.storage, (id, &info.sender), &Ballot { vote, weight })?;
 
-    pub fn increment(value: u64) -> StdResult<ValueResp> {
-        Ok(ValueResp { value: value + 1 })
-    }
-}
+        let resp = Response::new()
+            .add_attribute("action", "vote")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string())
+            .add_attribute("weight", weight.to_string());
 
-pub mod exec {
-    use cosmwasm_std::{BankMsg, Coin, DepsMut, Env, MessageInfo, Response, to_binary, Uint128, WasmMsg};
+        Ok(resp)
+    }
 
-    use crate::error::ContractError;
-    use crate::msg::ExecMsg;
-    use crate::state::{PARENT_DONATION, STATE};
+    pub fn execute_proposal(deps: DepsMut, env: Env, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+        let mut proposal = PROPOSALS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::ProposalNotFound { id })?;
+        let governance = GOVERNANCE.load(deps.storage)?;
+        if proposal_status(&proposal, &governance, env.block.time) != ProposalStatus::Passed {
+            return Err(ContractError::ProposalNotPassed { id });
+        }
 
-    pub fn donate(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError>  {
-        let mut state = STATE.load(deps.storage)?;
-        let mut resp = Response::new();
+        proposal.status = ProposalStatus::Executed;
+        PROPOSALS.save(deps.storage, id, &proposal)?;
 
-        if state.minimal_donation.amount.is_zero()
-            || info.funds.iter().any(|coin| {
-            coin.denom == state.minimal_donation.denom
-                && coin.amount >= state.minimal_donation.amount
-        })
-        {
-            state.counter += 1;
+        let resp = match proposal.action {
+            ProposalAction::Withdraw { recipient, funds } => {
+                let mut balance = available_balance(deps.as_ref(), &env)?;
+                let mut tokens = available_tokens(deps.as_ref(), &env)?;
 
-            if let Some(parent) = &mut state.donating_parent {
-                *parent -= 1;
-
-                if *parent == 0 {
-                    let parent_donation = PARENT_DONATION.load(deps.storage)?;
-                    *parent = parent_donation.donating_parent_period;
-
-                    let funds: Vec<_> = deps
-                        .querier
-                        .query_all_balances(env.contract.address)?
-                        .into_iter()
-                        .map(|mut coin| {
-                            coin.amount = coin.amount * parent_donation.part;
-                            coin
-                        })
-                        .collect();
-
-                    let msg = WasmMsg::Execute {
-                        contract_addr: parent_donation.address.to_string(),
-                        msg: to_binary(&ExecMsg::Donate {})?,
-                        funds,
-                    };
-
-                    resp = resp
-                        .add_message(msg)
-                        .add_attribute("donated_to_parent", parent_donation.address.to_string());
+                if let Some(funds) = &funds {
+                    limit_balances(&mut balance, &mut tokens, funds);
                 }
+
+                Response::new()
+                    .add_messages(payout(recipient.as_str(), balance, tokens)?)
+                    .add_attribute("recipient", recipient.as_str())
             }
+            ProposalAction::UpdateParents { parents } => {
+                let event = super::update_config(deps, &env, None, None, Some(parents))?;
+                Response::new().add_event(event)
+            }
+        };
+
+        let resp = resp
+            .add_attribute("action", "execute")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("id", id.to_string());
 
-            STATE.save(deps.storage, &state)?;
+        Ok(resp)
+    }
+
+    pub fn close_proposal(deps: DepsMut, env: Env, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+        let mut proposal = PROPOSALS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::ProposalNotFound { id })?;
+        let governance = GOVERNANCE.load(deps.storage)?;
+        if proposal.status != ProposalStatus::Open
+            || proposal_status(&proposal, &governance, env.block.time) != ProposalStatus::Rejected
+        {
+            return Err(ContractError::ProposalNotClosable { id });
         }
 
-        resp = resp
-            .add_attribute("action", "poke")
+        proposal.status = ProposalStatus::Rejected;
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "close")
             .add_attribute("sender", info.sender.as_str())
-            .add_attribute("counter", state.counter.to_string());
+            .add_attribute("id", id.to_string());
 
         Ok(resp)
     }
 
-    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    pub fn delegate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        validator: String,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
         let state = STATE.load(deps.storage)?;
-        if state.owner != info.sender {
-            return Err(ContractError::Unauthorized { owner: state.owner.to_string() });
-        }
+        ensure_owner(&state, &info.sender)?;
 
-        let balance = deps.querier.query_all_balances(&env.contract.address)?;
+        let staking = STAKING
+            .may_load(deps.storage)?
+            .ok_or(ContractError::StakingDisabled)?;
 
-        let bank_msg = BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: balance,
+        let amount_of = |coins: Vec<Coin>| {
+            coins
+                .into_iter()
+                .find(|coin| coin.denom == staking.denom)
+                .map(|coin| coin.amount)
+                .unwrap_or_default()
         };
+        let liquid = deps.querier.query_balance(&env.contract.address, &staking.denom)?.amount;
+        let unreserved = amount_of(available_balance(deps.as_ref(), &env)?);
+        let bonded = amount_of(bonded_balance(deps.as_ref(), &env)?);
+
+        // Funds reserved for campaign refunds stay liquid
+        let limit = (liquid + bonded) * staking.max_part;
+        let available = std::cmp::min(unreserved, limit.saturating_sub(bonded));
+        if amount > available {
+            return Err(ContractError::StakingLimitExceeded { available });
+        }
 
         let resp = Response::new()
-            .add_message(bank_msg)
-            .add_attribute("action", "withdraw")
+            .add_message(StakingMsg::Delegate {
+                validator: validator.clone(),
+                amount: Coin {
+                    denom: staking.denom,
+                    amount,
+                },
+            })
+            .add_attribute("action", "delegate")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("validator", validator)
+            .add_attribute("amount", amount.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn undelegate(
+        deps: DepsMut,
+        info: MessageInfo,
+        validator: String,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        let staking = STAKING
+            .may_load(deps.storage)?
+            .ok_or(ContractError::StakingDisabled)?;
+
+        let resp = Response::new()
+            .add_message(StakingMsg::Undelegate {
+                validator: validator.clone(),
+                amount: Coin {
+                    denom: staking.denom,
+                    amount,
+                },
+            })
+            .add_attribute("action", "undelegate")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("validator", validator)
+            .add_attribute("amount", amount.to_string());
+
+        Ok(resp)
+    }
+
+    pub fn claim_staking_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        let msgs = deps
+            .querier
+            .query_all_delegations(&env.contract.address)?
+            .into_iter()
+            .map(|delegation| DistributionMsg::WithdrawDelegatorReward {
+                validator: delegation.validator,
+            });
+
+        let resp = Response::new()
+            .add_messages(msgs)
+            .add_attribute("action", "claim_staking_rewards")
             .add_attribute("sender", info.sender.as_str());
 
         Ok(resp)
     }
 
-    pub fn withdraw_to(deps: DepsMut, env: Env, info: MessageInfo, recipient: String, funds: Option<Vec<Coin>>) -> Result<Response, ContractError> {
+    pub fn retry_parent_forward(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let pending = PENDING_FORWARDS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        if pending.is_empty() {
+            return Err(ContractError::NoPendingForwards);
+        }
+
+        let mut resp = Response::new()
+            .add_attribute("action", "retry_parent_forward")
+            .add_attribute("sender", info.sender.as_str());
+
+        let mut next_index = 0;
+        for (parent, forward) in pending {
+            PENDING_FORWARDS.remove(deps.storage, &parent);
+
+            let msgs = forward_to_parent(deps.storage, &mut next_index, &parent, forward.funds, forward.tokens)?;
+
+            resp = resp
+                .add_submessages(msgs)
+                .add_attribute("donated_to_parent", parent.to_string());
+        }
+
+        Ok(resp)
+    }
+
+    pub fn propose_owner(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        new_owner: String,
+        expiry: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
         let state = STATE.load(deps.storage)?;
-        if state.owner != info.sender {
-            return Err(ContractError::Unauthorized { owner: state.owner.to_string() });
+        ensure_owner(&state, &info.sender)?;
+
+        if expiry.map_or(false, |expiry| expiry.is_expired(&env.block)) {
+            return Err(ContractError::OwnershipProposalExpired);
         }
 
-        let mut balance = deps.querier.query_all_balances(&env.contract.address)?;
+        let new_owner = deps.api.addr_validate(&new_owner)?;
+        PENDING_OWNER.save(
+            deps.storage,
+            &PendingOwner {
+                new_owner: new_owner.clone(),
+                expiry,
+            },
+        )?;
 
-        if funds.is_some() {
-            let funds = funds.unwrap();
-            if !funds.is_empty() {
-                for coin in &mut balance {
-                    let limit = funds.
-                        iter().
-                        find(|c| c.denom == coin.denom).
-                        map(|c| c.amount).
-                        unwrap_or(Uint128::zero());
+        let resp = Response::new()
+            .add_attribute("action", "propose_owner")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("new_owner", new_owner.as_str());
 
-                    coin.amount = std::cmp::min(coin.amount, limit);
-                }
-            }
+        Ok(resp)
+    }
+
+    pub fn accept_ownership(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let pending = PENDING_OWNER
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoOwnershipProposal)?;
+
+        if pending.new_owner != info.sender {
+            return Err(ContractError::Unauthorized { owner: pending.new_owner.to_string() });
         }
 
+        if pending.expiry.map_or(false, |expiry| expiry.is_expired(&env.block)) {
+            return Err(ContractError::OwnershipProposalExpired);
+        }
 
-        let bank_msg = BankMsg::Send {
-            to_address: recipient,
-            amount: balance,
-        };
+        STATE.update(deps.storage, |mut state| -> StdResult<_> {
+            state.owner = Some(pending.new_owner);
+            Ok(state)
+        })?;
+        PENDING_OWNER.remove(deps.storage);
 
         let resp = Response::new()
-            .add_message(bank_msg)
-            .add_attribute("action", "withdraw")
+            .add_attribute("action", "accept_ownership")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn cancel_ownership_proposal(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+            return Err(ContractError::NoOwnershipProposal);
+        }
+        PENDING_OWNER.remove(deps.storage);
+
+        let resp = Response::new()
+            .add_attribute("action", "cancel_ownership_proposal")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn renounce_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+        ensure_owner(&state, &info.sender)?;
+
+        state.owner = None;
+        STATE.save(deps.storage, &state)?;
+        PENDING_OWNER.remove(deps.storage);
+
+        let resp = Response::new()
+            .add_attribute("action", "renounce_ownership")
             .add_attribute("sender", info.sender.as_str());
 
         Ok(resp)
     }
 }
 
-pub fn migrate(mut deps: DepsMut) -> Result<Response, ContractError> {
+pub mod reply {
+    use cosmwasm_std::{DepsMut, Reply, Response, StdResult, SubMsgResult};
+    use cw_utils::parse_reply_instantiate_data;
+
+    use crate::error::ContractError;
+    use crate::msg::HookErrorPolicy;
+    use crate::state::{FORWARDS_IN_FLIGHT, HOOKS, HOOKS_IN_FLIGHT, PENDING_FORWARDS, REWARD_TOKEN};
+
+    use super::{add_coins, add_tokens};
+
+    pub fn parent_forward(deps: DepsMut, id: u64, result: SubMsgResult) -> Result<Response, ContractError> {
+        let forward = FORWARDS_IN_FLIGHT.load(deps.storage, id)?;
+        FORWARDS_IN_FLIGHT.remove(deps.storage, id);
+
+        let error = match result {
+            SubMsgResult::Ok(_) => return Ok(Response::new()),
+            SubMsgResult::Err(error) => error,
+        };
+
+        PENDING_FORWARDS.update(deps.storage, &forward.parent, |pending| -> StdResult<_> {
+            let mut pending = pending.unwrap_or_default();
+            add_coins(&mut pending.funds, &forward.funds);
+            add_tokens(&mut pending.tokens, &forward.tokens);
+            Ok(pending)
+        })?;
+
+        let resp = Response::new()
+            .add_attribute("action", "parent_forward_failed")
+            .add_attribute("parent", forward.parent.as_str())
+            .add_attribute("error", error);
+
+        Ok(resp)
+    }
+
+    pub fn donation_hook(deps: DepsMut, id: u64, result: SubMsgResult) -> Result<Response, ContractError> {
+        let hook = HOOKS_IN_FLIGHT.load(deps.storage, id)?;
+        HOOKS_IN_FLIGHT.remove(deps.storage, id);
+
+        let error = match result {
+            SubMsgResult::Ok(_) => return Ok(Response::new()),
+            SubMsgResult::Err(error) => error,
+        };
+
+        let mut resp = Response::new()
+            .add_attribute("action", "donation_hook_failed")
+            .add_attribute("hook", hook.as_str())
+            .add_attribute("error", error);
+
+        if HOOKS.may_load(deps.storage, &hook)? == Some(HookErrorPolicy::Remove) {
+            HOOKS.remove(deps.storage, &hook);
+            resp = resp.add_attribute("hook_removed", "true");
+        }
+
+        Ok(resp)
+    }
+
+    pub fn reward_token(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+        let resp = parse_reply_instantiate_data(msg)?;
+        let token = deps.api.addr_validate(&resp.contract_address)?;
+        REWARD_TOKEN.save(deps.storage, &token)?;
+
+        Ok(Response::new().add_attribute("reward_token", token.as_str()))
+    }
+}
+
+pub mod sudo {
+    use cosmwasm_std::{DepsMut, Env, Response, StdResult};
+
+    use crate::error::ContractError;
+    use crate::state::{COUNTER_HISTORY, PAUSED, PENDING_OWNER, STATE};
+
+    use super::{available_balance, available_tokens, payout};
+
+    pub fn force_owner(deps: DepsMut, owner: String) -> Result<Response, ContractError> {
+        let owner = deps.api.addr_validate(&owner)?;
+
+        STATE.update(deps.storage, |mut state| -> StdResult<_> {
+            state.owner = Some(owner.clone());
+            Ok(state)
+        })?;
+        PENDING_OWNER.remove(deps.storage);
+
+        let resp = Response::new()
+            .add_attribute("action", "force_owner")
+            .add_attribute("owner", owner.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn force_pause(deps: DepsMut) -> Result<Response, ContractError> {
+        PAUSED.save(deps.storage, &true)?;
+
+        Ok(Response::new().add_attribute("action", "force_pause"))
+    }
+
+    pub fn force_withdraw(deps: DepsMut, env: Env, community_pool: String) -> Result<Response, ContractError> {
+        let community_pool = deps.api.addr_validate(&community_pool)?;
+
+        // Campaign refunds stay claimable by the donors
+        let balance = available_balance(deps.as_ref(), &env)?;
+        let tokens = available_tokens(deps.as_ref(), &env)?;
+
+        let resp = Response::new()
+            .add_messages(payout(community_pool.as_str(), balance, tokens)?)
+            .add_attribute("action", "force_withdraw")
+            .add_attribute("recipient", community_pool.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn reset_counter(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+        STATE.update(deps.storage, |mut state| -> StdResult<_> {
+            state.counter = 0;
+            Ok(state)
+        })?;
+        COUNTER_HISTORY.save(deps.storage, &0, env.block.height)?;
+
+        Ok(Response::new().add_attribute("action", "reset_counter"))
+    }
+}
+
+type MigrationStep = fn(DepsMut) -> StdResult<()>;
+
+/// Every step upgrades the storage of one released version to the layout of the next one,
+/// so older contracts are migrated by chaining all the steps in between
+const MIGRATIONS: &[(&str, &str, MigrationStep)] = &[
+    ("0.1.0", "0.2.0", migrate_0_1_0),
+    ("0.2.0", "0.3.0", migrate_0_2_0),
+    ("0.3.0", "0.4.0", migrate_0_3_0),
+];
+
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage).
         unwrap_or_else(|_| ContractVersion { contract: CONTRACT_NAME.to_string(), version: String::from("0.1.0") });
 
@@ -188,45 +2647,101 @@ pub fn migrate(mut deps: DepsMut) -> Result<Response, ContractError> {
         });
     }
 
-    let resp = match contract_version.version.as_str() {
-        "0.1.0" => migrate_0_1_0(deps.branch()).map_err(ContractError::from)?,
-        "0.2.0" => migrate_0_2_0(deps.branch()).map_err(ContractError::from)?,
-        CONTRACT_VERSION => return Ok(Response::default()),
-        version => {
-            return Err(ContractError::InvalidContractVersion {
-                version: version.into(),
-            })
-        }
-    };
+    let stored = Version::parse(&contract_version.version).map_err(|_| ContractError::InvalidContractVersion {
+        version: contract_version.version.clone(),
+    })?;
+    let current = Version::parse(CONTRACT_VERSION).map_err(|err| StdError::generic_err(err.to_string()))?;
+    if stored > current {
+        return Err(ContractError::MigrationDowngrade {
+            from: stored.to_string(),
+            to: current.to_string(),
+        });
+    }
+
+    let mut resp = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", &contract_version.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+
+    let mut version = contract_version.version;
+    while version != CONTRACT_VERSION {
+        let (_, next, step) = MIGRATIONS
+            .iter()
+            .find(|(from, _, _)| *from == version)
+            .ok_or_else(|| ContractError::InvalidContractVersion { version: version.clone() })?;
+
+        step(deps.branch())?;
+        version = next.to_string();
+    }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    seed_history(deps.branch(), &env)?;
+
+    let MigrateMsg {
+        minimal_donations,
+        insufficient_donation,
+        parents,
+    } = msg;
+    if minimal_donations.is_some() || insufficient_donation.is_some() || parents.is_some() {
+        let event = update_config(deps, &env, minimal_donations, insufficient_donation, parents)?;
+        resp = resp.add_event(event);
+    }
 
     Ok(resp)
 }
 
-pub fn migrate_0_1_0(deps: DepsMut) -> Result<Response, ContractError> {
+/// Starts the counter history and the donation totals of contracts that predate them, with
+/// the totals taken from the donor ledger
+fn seed_history(deps: DepsMut, env: &Env) -> StdResult<()> {
+    if COUNTER_HISTORY.may_load(deps.storage)?.is_some() {
+        return Ok(());
+    }
+
+    let counter = STATE.load(deps.storage)?.counter;
+    COUNTER_HISTORY.save(deps.storage, &counter, env.block.height)?;
+
+    let mut totals = vec![];
+    for item in DONORS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, record) = item?;
+        add_coins(&mut totals, &record.amounts);
+    }
+    for coin in totals {
+        DONATION_TOTALS.save(deps.storage, &coin.denom, &coin.amount, env.block.height)?;
+    }
+
+    Ok(())
+}
+
+fn migrate_0_1_0(deps: DepsMut) -> StdResult<()> {
+    #[derive(Serialize, Deserialize)]
+    struct NewState {
+        counter: u64,
+        minimal_donation: Coin,
+        owner: Addr,
+    }
+
     const COUNTER: Item<u64> = Item::new("counter");
     const MINIMAL_DONATION: Item<Coin> = Item::new("minimal_donation");
     const OWNER: Item<Addr> = Item::new("owner");
+    const NEW_STATE: Item<NewState> = Item::new("state");
 
     let counter = COUNTER.load(deps.storage)?;
     let minimal_donation = MINIMAL_DONATION.load(deps.storage)?;
     let owner = OWNER.load(deps.storage)?;
 
-    STATE.save(
+    NEW_STATE.save(
         deps.storage,
-        &State {
+        &NewState {
             counter,
             minimal_donation,
             owner,
-            donating_parent: None,
         },
     )?;
 
-    Ok(Response::new())
+    Ok(())
 }
 
-pub fn migrate_0_2_0(deps: DepsMut) -> StdResult<Response> {
+fn migrate_0_2_0(deps: DepsMut) -> StdResult<()> {
     #[derive(Serialize, Deserialize)]
     struct OldState {
         counter: u64,
@@ -234,7 +2749,16 @@ pub fn migrate_0_2_0(deps: DepsMut) -> StdResult<Response> {
         owner: Addr,
     }
 
+    #[derive(Serialize, Deserialize)]
+    struct NewState {
+        counter: u64,
+        minimal_donation: Coin,
+        owner: Addr,
+        donating_parent: Option<u64>,
+    }
+
     const OLD_STATE: Item<OldState> = Item::new("state");
+    const NEW_STATE: Item<NewState> = Item::new("state");
 
     let OldState {
         counter,
@@ -242,9 +2766,9 @@ pub fn migrate_0_2_0(deps: DepsMut) -> StdResult<Response> {
         owner,
     } = OLD_STATE.load(deps.storage)?;
 
-    STATE.save(
+    NEW_STATE.save(
         deps.storage,
-        &State {
+        &NewState {
             counter,
             minimal_donation,
             owner,
@@ -252,5 +2776,72 @@ pub fn migrate_0_2_0(deps: DepsMut) -> StdResult<Response> {
         },
     )?;
 
-    Ok(Response::new())
+    Ok(())
+}
+
+fn migrate_0_3_0(deps: DepsMut) -> StdResult<()> {
+    #[derive(Serialize, Deserialize)]
+    struct OldState {
+        counter: u64,
+        minimal_donation: Coin,
+        owner: Addr,
+        donating_parent: Option<u64>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct OldParentDonation {
+        address: Addr,
+        donating_parent_period: u64,
+        part: Decimal,
+    }
+
+    const OLD_STATE: Item<OldState> = Item::new("state");
+    const OLD_PARENT_DONATION: Item<OldParentDonation> = Item::new("parent_donation");
+
+    let OldState {
+        counter,
+        minimal_donation,
+        owner,
+        donating_parent,
+    } = OLD_STATE.load(deps.storage)?;
+
+    if let Some(parent) = OLD_PARENT_DONATION.may_load(deps.storage)? {
+        PARENTS.save(
+            deps.storage,
+            &parent.address,
+            &ParentDonation {
+                address: parent.address.clone(),
+                donating_parent_period: parent.donating_parent_period,
+                part: parent.part,
+                donations_left: donating_parent.unwrap_or(parent.donating_parent_period),
+                depth: 0,
+                period_kind: DonatingPeriodKind::Donations,
+                last_forward: 0,
+                unforwarded: vec![],
+                unforwarded_tokens: vec![],
+                forwarded_at: counter,
+            },
+        )?;
+        OLD_PARENT_DONATION.remove(deps.storage);
+    }
+
+    // A zero minimal donation used to count donations in any denom
+    let minimal_donations = if minimal_donation.amount.is_zero() {
+        vec![]
+    } else {
+        vec![minimal_donation]
+    };
+
+    STATE.save(
+        deps.storage,
+        &State {
+            counter,
+            minimal_donations,
+            owner: Some(owner),
+            // Donations below the minimum used to be kept without being counted
+            insufficient_donation: InsufficientDonationPolicy::Keep,
+        },
+    )?;
+
+    Ok(())
 }
\ No newline at end of file
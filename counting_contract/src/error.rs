@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, Decimal, StdError, Timestamp, Uint128};
+use cw_utils::ParseReplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,6 +7,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
     #[error("Unauthorized - only {owner} can call it")]
     Unauthorized { owner: String },
 
@@ -14,4 +18,152 @@ pub enum ContractError {
 
     #[error("Unsupported contract version for migration: {version}")]
     InvalidContractVersion { version: String },
-}
\ No newline at end of file
+
+    #[error("Cannot migrate from version {from} down to {to}")]
+    MigrationDowngrade { from: String, to: String },
+
+    #[error("Donation is below all the minimal donations: {}", coins_to_string(.minimal))]
+    InsufficientDonation { minimal: Vec<Coin> },
+
+    #[error("Donations in {denom} are not accepted")]
+    UnacceptedDenom { denom: String },
+
+    #[error("Minimal donation for {denom} is configured more than once")]
+    DuplicatedDenom { denom: String },
+
+    #[error("Withdrawal shares cannot be empty")]
+    NoWithdrawShares,
+
+    #[error("Withdrawal shares sum up to {total}, but must sum up to 1")]
+    InvalidWithdrawShares { total: Decimal },
+
+    #[error("Recipient {address} is given more than one share")]
+    DuplicatedRecipient { address: String },
+
+    #[error("Withdrawals are time-locked for {timelock} seconds and have to be requested")]
+    WithdrawalTimelocked { timelock: u64 },
+
+    #[error("There is no pending withdrawal with id {id}")]
+    WithdrawalNotFound { id: u64 },
+
+    #[error("Withdrawal {id} is locked until {unlock}")]
+    WithdrawalLocked { id: u64, unlock: Timestamp },
+
+    #[error("Withdrawals are decided by donor proposals")]
+    WithdrawalsGoverned,
+
+    #[error("Parents are decided by donor proposals")]
+    ParentsGoverned,
+
+    #[error("Invalid governance config")]
+    InvalidGovernanceConfig,
+
+    #[error("Governance is not enabled")]
+    GovernanceDisabled,
+
+    #[error("No voting weight")]
+    NoVotingWeight,
+
+    #[error("Proposal {id} not found")]
+    ProposalNotFound { id: u64 },
+
+    #[error("Proposal {id} is not open for voting")]
+    ProposalNotOpen { id: u64 },
+
+    #[error("Already voted on proposal {id}")]
+    AlreadyVoted { id: u64 },
+
+    #[error("Proposal {id} has not passed")]
+    ProposalNotPassed { id: u64 },
+
+    #[error("Proposal {id} cannot be closed")]
+    ProposalNotClosable { id: u64 },
+
+    #[error("Invalid staking config")]
+    InvalidStakingConfig,
+
+    #[error("Staking is not enabled")]
+    StakingDisabled,
+
+    #[error("Staking limit exceeded, {available} left to delegate")]
+    StakingLimitExceeded { available: Uint128 },
+
+    #[error("Parent {address} leads back to this contract")]
+    ParentCycle { address: String },
+
+    #[error("Parent chain is deeper than {max_depth}")]
+    ParentChainTooDeep { max_depth: u32 },
+
+    #[error("Contract is paused")]
+    Paused,
+
+    #[error("Campaign must end after it starts and in the future")]
+    InvalidCampaignPeriod,
+
+    #[error("Campaign target must be non-zero")]
+    InvalidCampaignTarget,
+
+    #[error("Campaign overlaps with the campaign {id}")]
+    CampaignOverlap { id: u64 },
+
+    #[error("There is no campaign with id {id}")]
+    CampaignNotFound { id: u64 },
+
+    #[error("Campaign {id} cannot be refunded")]
+    CampaignNotRefundable { id: u64 },
+
+    #[error("Campaign {id} has no rewards to claim")]
+    CampaignNotRewardable { id: u64 },
+
+    #[error("There are no donations to the campaign {id} to claim for")]
+    NoCampaignDonation { id: u64 },
+
+    #[error("Ownership has been renounced")]
+    OwnershipRenounced,
+
+    #[error("There is no pending ownership proposal")]
+    NoOwnershipProposal,
+
+    #[error("Ownership proposal has expired")]
+    OwnershipProposalExpired,
+
+    #[error("Parent {address} is configured more than once")]
+    DuplicatedParent { address: String },
+
+    #[error("Parent parts sum up to {total}, which is more than 1")]
+    InvalidParentParts { total: Decimal },
+
+    #[error("Parent {address} must have a non-zero donating period")]
+    InvalidDonatingPeriod { address: String },
+
+    #[error("Token {token} is not accepted for donations")]
+    UnacceptedToken { token: String },
+
+    #[error("Token {token} is configured more than once")]
+    DuplicatedToken { token: String },
+
+    #[error("There are no pending parent forwards to retry")]
+    NoPendingForwards,
+
+    #[error("Hook {address} is already registered")]
+    HookAlreadyRegistered { address: String },
+
+    #[error("Hook {address} is not registered")]
+    HookNotRegistered { address: String },
+
+    #[error("Milestone {id} is configured more than once")]
+    DuplicatedMilestone { id: String },
+
+    #[error("Milestone {id} can never be reached")]
+    InvalidMilestone { id: String },
+
+    #[error("Milestone {id} ranks more than the {max} top donors tracked")]
+    TopDonorsRankTooHigh { id: String, max: u32 },
+
+    #[error("Unrecognized reply id: {id}")]
+    UnrecognizedReplyId { id: u64 },
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins.iter().map(Coin::to_string).collect::<Vec<_>>().join(", ")
+}
@@ -1,7 +1,7 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
-use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult, to_binary};
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult, to_binary};
 use crate::error::ContractError;
 
 mod contract;
@@ -12,18 +12,38 @@ mod error;
 pub mod multitest;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, msg: msg::InstantiateMsg) -> StdResult<Response> {
-    contract::instantiate(deps, msg, info)
+pub fn instantiate(deps: DepsMut, env: Env, info: MessageInfo, msg: msg::InstantiateMsg) -> Result<Response, ContractError> {
+    contract::instantiate(deps, env, msg, info)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use msg::QueryMsg::*;
     use contract::query;
 
     match msg {
         Value {} => to_binary(&query::value(deps)?),
+        ValueAt { height } => to_binary(&query::value_at(deps, height)?),
         Increment { number } => to_binary(&query::increment(number)?),
+        Donor { address } => to_binary(&query::donor(deps, address)?),
+        Donors { start_after, limit } => to_binary(&query::donors(deps, start_after, limit)?),
+        Ownership {} => to_binary(&query::ownership(deps)?),
+        Parents {} => to_binary(&query::parents(deps)?),
+        Config {} => to_binary(&query::config(deps)?),
+        ParentStatus {} => to_binary(&query::parent_status(deps)?),
+        PendingWithdrawals {} => to_binary(&query::pending_withdrawals(deps)?),
+        Status {} => to_binary(&query::status(deps)?),
+        Campaign { id } => to_binary(&query::campaign(deps, env, id)?),
+        Campaigns {} => to_binary(&query::campaigns(deps, env)?),
+        Hooks {} => to_binary(&query::hooks(deps)?),
+        Milestones {} => to_binary(&query::milestones(deps)?),
+        Badges { donor } => to_binary(&query::badges(deps, donor)?),
+        RewardToken {} => to_binary(&query::reward_token(deps)?),
+        Proposal { id } => to_binary(&query::proposal(deps, env, id)?),
+        Proposals { start_after, limit } => to_binary(&query::proposals(deps, env, start_after, limit)?),
+        Votes { id, start_after, limit } => to_binary(&query::votes(deps, id, start_after, limit)?),
+        Staking {} => to_binary(&query::staking(deps, env)?),
+        Parent {} => to_binary(&query::parent(deps)?),
     }
 }
 
@@ -32,14 +52,73 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: msg::ExecMsg) ->
     use contract::exec;
     use msg::ExecMsg::*;
 
+    if !matches!(msg, Pause {} | Unpause {} | EmergencyWithdraw {}) {
+        contract::ensure_not_paused(deps.storage)?;
+    }
+
     match msg {
         Donate {} => exec::donate(deps, env, info),
+        Receive(msg) => exec::receive(deps, env, info, msg),
         Withdraw {} => exec::withdraw(deps, env, info),
         WithdrawTo { recipient, funds } => exec::withdraw_to(deps, env, info, recipient, funds),
+        WithdrawSplit { shares } => exec::withdraw_split(deps, env, info, shares),
+        RequestWithdraw { recipient, funds } => exec::request_withdraw(deps, env, info, recipient, funds),
+        ExecuteWithdraw { id } => exec::execute_withdraw(deps, env, info, id),
+        CancelWithdraw { id } => exec::cancel_withdraw(deps, info, id),
+        ProposeOwner { new_owner, expiry } => exec::propose_owner(deps, env, info, new_owner, expiry),
+        AcceptOwnership {} => exec::accept_ownership(deps, env, info),
+        CancelOwnershipProposal {} => exec::cancel_ownership_proposal(deps, info),
+        RenounceOwnership {} => exec::renounce_ownership(deps, info),
+        RetryParentForward {} => exec::retry_parent_forward(deps, info),
+        UpdateConfig { minimal_donations, insufficient_donation, parents } => {
+            exec::update_config(deps, env, info, minimal_donations, insufficient_donation, parents)
+        }
+        Pause {} => exec::pause(deps, info),
+        Unpause {} => exec::unpause(deps, info),
+        EmergencyWithdraw {} => exec::emergency_withdraw(deps, env, info),
+        OpenCampaign { target, start, end, kind } => exec::open_campaign(deps, env, info, target, start, end, kind),
+        ClaimCampaignRefund { id } => exec::claim_campaign_refund(deps, env, info, id),
+        ClaimCampaignReward { id } => exec::claim_campaign_reward(deps, env, info, id),
+        AddHook { address, on_error } => exec::add_hook(deps, info, address, on_error),
+        RemoveHook { address } => exec::remove_hook(deps, info, address),
+        ConfigureBadges { collection, milestones } => exec::configure_badges(deps, info, collection, milestones),
+        Propose { description, recipient, funds } => exec::propose(deps, env, info, description, recipient, funds),
+        ProposeParents { description, parents } => exec::propose_parents(deps, env, info, description, parents),
+        Vote { id, vote } => exec::vote(deps, env, info, id, vote),
+        Execute { id } => exec::execute_proposal(deps, env, info, id),
+        Close { id } => exec::close_proposal(deps, env, info, id),
+        Delegate { validator, amount } => exec::delegate(deps, env, info, validator, amount),
+        Undelegate { validator, amount } => exec::undelegate(deps, info, validator, amount),
+        ClaimStakingRewards {} => exec::claim_staking_rewards(deps, env, info),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    use contract::{DONATION_HOOK_REPLY, PARENT_FORWARD_REPLY, REWARD_TOKEN_REPLY, reply, reply_kind};
+
+    match reply_kind(msg.id) {
+        PARENT_FORWARD_REPLY => reply::parent_forward(deps, msg.id, msg.result),
+        DONATION_HOOK_REPLY => reply::donation_hook(deps, msg.id, msg.result),
+        REWARD_TOKEN_REPLY => reply::reward_token(deps, msg),
+        _ => Err(ContractError::UnrecognizedReplyId { id: msg.id }),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: msg::SudoMsg) -> Result<Response, ContractError> {
+    use contract::sudo;
+    use msg::SudoMsg::*;
+
+    match msg {
+        ForceOwner { owner } => sudo::force_owner(deps, owner),
+        ForcePause {} => sudo::force_pause(deps),
+        ForceWithdraw { community_pool } => sudo::force_withdraw(deps, env, community_pool),
+        ResetCounter {} => sudo::reset_counter(deps, env),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-    contract::migrate(deps)
+pub fn migrate(deps: DepsMut, env: Env, msg: msg::MigrateMsg) -> Result<Response, ContractError> {
+    contract::migrate(deps, env, msg)
 }
\ No newline at end of file
@@ -1,18 +1,237 @@
+use std::fmt;
+
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, Decimal};
+use cosmwasm_std::{Addr, Coin, Decimal, StdResult, Timestamp, to_binary, Uint128, WasmMsg};
+use cw20::{Cw20CoinVerified, Cw20ReceiveMsg};
+use cw_utils::Expiration;
 
 #[cw_serde]
 pub struct Parent {
     pub addr: String,
     pub donating_period: u64,
     pub part: Decimal,
+    #[serde(default)]
+    pub period_kind: DonatingPeriodKind,
+}
+
+/// What the `donating_period` of a parent is measured in
+#[cw_serde]
+#[derive(Default)]
+pub enum DonatingPeriodKind {
+    /// Every `donating_period`-th counted donation is forwarded
+    #[default]
+    Donations,
+    /// The first donation at least `donating_period` seconds after the previous forward is forwarded
+    Seconds,
+    /// The first donation at least `donating_period` blocks after the previous forward is forwarded
+    Blocks,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum InsufficientDonationPolicy {
+    /// Fail the donation, so the funds never leave the donor
+    Reject,
+    /// Send the funds back to the donor
+    #[default]
+    Refund,
+    /// Keep the funds as a tip without incrementing the counter
+    Keep,
+}
+
+impl fmt::Display for InsufficientDonationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsufficientDonationPolicy::Reject => write!(f, "reject"),
+            InsufficientDonationPolicy::Refund => write!(f, "refund"),
+            InsufficientDonationPolicy::Keep => write!(f, "keep"),
+        }
+    }
+}
+
+#[cw_serde]
+pub enum CampaignKind {
+    /// Donors can claim their donations back if the target is missed
+    AllOrNothing,
+    /// The contract keeps the donations whether the target is met or not
+    KeepItAll,
+}
+
+#[cw_serde]
+pub enum CampaignStatus {
+    Upcoming,
+    Active,
+    Succeeded,
+    Failed,
+}
+
+/// What happens to a donation when a hook fails to process it
+#[cw_serde]
+#[derive(Default)]
+pub enum HookErrorPolicy {
+    /// Fail the donation together with the hook
+    Revert,
+    /// Keep the donation and report the failure in the `donation_hook_failed` event
+    #[default]
+    Ignore,
+    /// Keep the donation and unregister the hook
+    Remove,
+}
+
+/// Sent to every registered hook after a counted donation
+#[cw_serde]
+pub struct DonationHookMsg {
+    pub donor: Addr,
+    pub funds: Vec<Coin>,
+    pub counter: u64,
+}
+
+impl DonationHookMsg {
+    /// Wraps the message in `DonationHookExecuteMsg`, the variant hook contracts have to
+    /// accept in their own execute messages
+    pub fn into_wasm_msg(self, contract_addr: impl Into<String>) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: to_binary(&DonationHookExecuteMsg::DonationHook(self))?,
+            funds: vec![],
+        })
+    }
+}
+
+#[cw_serde]
+pub enum DonationHookExecuteMsg {
+    DonationHook(DonationHookMsg),
+}
+
+#[cw_serde]
+pub enum MilestoneRule {
+    /// Reached with the donor's `count`-th counted donation
+    Donations { count: u64 },
+    /// Reached when the donor's cumulative donations get to `amount`
+    Amount { amount: Coin },
+    /// Reached when the donor's cumulative donations in `denom` rank in the top `rank` donors,
+    /// with `rank` of at most 20
+    TopDonors { denom: String, rank: u32 },
+}
+
+/// A badge is minted once per donor for every milestone they reach
+#[cw_serde]
+pub struct Milestone {
+    pub id: String,
+    pub rule: MilestoneRule,
+    pub token_uri: Option<String>,
+}
+
+/// Reward tokens minted per unit of `denom` donated
+#[cw_serde]
+pub struct RewardRate {
+    pub denom: String,
+    pub rate: Decimal,
+}
+
+/// Cw20 reward token instantiated together with the contract, which becomes its minter.
+/// `code_id` has to point to a cw20-base compatible contract.
+#[cw_serde]
+pub struct RewardTokenInit {
+    pub code_id: u64,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub rates: Vec<RewardRate>,
+    pub donor_cap: Option<Uint128>,
+    pub total_cap: Option<Uint128>,
+}
+
+/// Withdrawals are decided by donors voting with their counted donations in `denom`.
+/// `quorum` is the part of the total weight which has to vote, and `threshold` the part of
+/// the non-abstaining votes which has to be in favour. Proposals are open for
+/// `voting_period` seconds.
+#[cw_serde]
+pub struct GovernanceConfig {
+    pub denom: String,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+}
+
+#[cw_serde]
+pub enum VoteOption {
+    Yes,
+    No,
+    Abstain,
+}
+
+#[cw_serde]
+pub enum ProposalStatus {
+    Open,
+    Passed,
+    Rejected,
+    Executed,
+}
+
+/// What a proposal does once it is executed
+#[cw_serde]
+pub enum ProposalAction {
+    /// Pays out with the same semantics as `WithdrawTo`
+    Withdraw { recipient: Addr, funds: Option<Vec<Coin>> },
+    /// Replaces all the parents like `UpdateConfig` does
+    UpdateParents { parents: Vec<Parent> },
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct Tally {
+    pub yes: Uint128,
+    pub no: Uint128,
+    pub abstain: Uint128,
+}
+
+/// Up to `max_part` of the contract's `denom` holdings, bonded ones included, can be delegated
+#[cw_serde]
+pub struct StakingConfig {
+    pub denom: String,
+    pub max_part: Decimal,
 }
 
 #[cw_serde]
+pub struct AcceptedToken {
+    pub address: String,
+    pub minimal_donation: Uint128,
+}
+
+#[cw_serde]
+#[derive(Default)]
 pub struct InstantiateMsg {
     pub counter: Option<u64>,
-    pub minimal_donation: Coin,
-    pub parent: Option<Parent>,
+    /// A donation counts when it meets any of the thresholds; other denoms are rejected.
+    /// No thresholds at all means that any donation counts.
+    pub minimal_donations: Vec<Coin>,
+    pub parents: Vec<Parent>,
+    pub insufficient_donation: Option<InsufficientDonationPolicy>,
+    pub accepted_tokens: Vec<AcceptedToken>,
+    /// Seconds a requested withdrawal waits before it can be executed. When set, the
+    /// instant withdrawals are disabled.
+    pub withdraw_timelock: Option<u64>,
+    /// Address allowed to pause the contract and trigger the emergency withdrawal, in
+    /// addition to the owner
+    pub guardian: Option<String>,
+    pub reward_token: Option<RewardTokenInit>,
+    /// Replaces the owner withdrawals with proposals voted on by donors
+    pub governance: Option<GovernanceConfig>,
+    pub staking: Option<StakingConfig>,
+    /// Longest parent chain a donation can be forwarded through, 5 by default. It is checked
+    /// whenever the parents are set, so chains which got deeper above them are not rejected.
+    pub max_parent_depth: Option<u32>,
+}
+
+/// Config values to apply once the storage is migrated, with the same semantics as
+/// `ExecMsg::UpdateConfig`
+#[cw_serde]
+#[derive(Default)]
+pub struct MigrateMsg {
+    pub minimal_donations: Option<Vec<Coin>>,
+    pub insufficient_donation: Option<InsufficientDonationPolicy>,
+    pub parents: Option<Vec<Parent>>,
 }
 
 #[cw_serde]
@@ -20,10 +239,73 @@ pub struct InstantiateMsg {
 pub enum QueryMsg {
     #[returns(ValueResp)]
     Value {},
+    /// Counter and counted donation totals at the end of the block `height`
+    #[returns(ValueAtResp)]
+    ValueAt {
+        height: u64,
+    },
     #[returns(ValueResp)]
     Increment {
         number: u64,
     },
+    /// Addresses which never donated get an empty record
+    #[returns(DonorResp)]
+    Donor {
+        address: String,
+    },
+    #[returns(DonorsResp)]
+    Donors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(OwnershipResp)]
+    Ownership {},
+    #[returns(ParentsResp)]
+    Parents {},
+    #[returns(ConfigResp)]
+    Config {},
+    #[returns(ParentStatusResp)]
+    ParentStatus {},
+    #[returns(PendingWithdrawalsResp)]
+    PendingWithdrawals {},
+    #[returns(StatusResp)]
+    Status {},
+    #[returns(CampaignResp)]
+    Campaign {
+        id: u64,
+    },
+    #[returns(CampaignsResp)]
+    Campaigns {},
+    #[returns(HooksResp)]
+    Hooks {},
+    #[returns(MilestonesResp)]
+    Milestones {},
+    #[returns(BadgesResp)]
+    Badges {
+        donor: String,
+    },
+    #[returns(RewardTokenResp)]
+    RewardToken {},
+    #[returns(ProposalResp)]
+    Proposal {
+        id: u64,
+    },
+    #[returns(ProposalsResp)]
+    Proposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(VotesResp)]
+    Votes {
+        id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(StakingResp)]
+    Staking {},
+    /// Used by children to walk the parent chain
+    #[returns(ParentResp)]
+    Parent {},
 }
 
 #[cw_serde]
@@ -31,12 +313,335 @@ pub struct ValueResp {
     pub value: u64,
 }
 
+/// `value` is `None` for heights before the history was recorded. Cw20 totals are listed
+/// in `donations` with the token address as denom.
+#[cw_serde]
+pub struct ValueAtResp {
+    pub height: u64,
+    pub value: Option<u64>,
+    pub donations: Vec<Coin>,
+}
+
+/// Cw20 donations are listed in `amounts` with the token address as denom
+#[cw_serde]
+pub struct DonorResp {
+    pub address: Addr,
+    pub donations: u64,
+    pub amounts: Vec<Coin>,
+    pub first_block: u64,
+    pub last_block: u64,
+}
+
+#[cw_serde]
+pub struct DonorsResp {
+    pub donors: Vec<DonorResp>,
+}
+
+#[cw_serde]
+pub struct OwnershipResp {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+    pub pending_expiry: Option<Expiration>,
+}
+
+#[cw_serde]
+pub struct ParentsResp {
+    pub parents: Vec<Parent>,
+}
+
+/// `depth` is the longest parent chain above the contract
+#[cw_serde]
+pub struct ParentResp {
+    pub parents: Vec<Addr>,
+    pub depth: u32,
+}
+
+#[cw_serde]
+pub struct ConfigResp {
+    pub owner: Option<Addr>,
+    pub minimal_donations: Vec<Coin>,
+    pub insufficient_donation: InsufficientDonationPolicy,
+    pub accepted_tokens: Vec<AcceptedToken>,
+    pub withdraw_timelock: Option<u64>,
+    pub governance: Option<GovernanceConfig>,
+    pub max_parent_depth: u32,
+}
+
+#[cw_serde]
+pub struct ParentProgress {
+    pub address: Addr,
+    pub donating_period: u64,
+    pub period_kind: DonatingPeriodKind,
+    /// Only counts down in the `Donations` period kind
+    pub donations_left: u64,
+    /// Time in seconds or block height from which the next donation is forwarded, `None` in
+    /// the `Donations` period kind
+    pub next_forward: Option<u64>,
+    pub part: Decimal,
+    /// Donations since the previous forward, the parent gets its `part` of them
+    pub unforwarded: Vec<Coin>,
+    pub unforwarded_tokens: Vec<Cw20CoinVerified>,
+    pub pending_forward: Vec<Coin>,
+    pub pending_tokens: Vec<Cw20CoinVerified>,
+}
+
+#[cw_serde]
+pub struct ParentStatusResp {
+    pub parents: Vec<ParentProgress>,
+}
+
+#[cw_serde]
+pub struct PendingWithdrawal {
+    pub id: u64,
+    pub recipient: Addr,
+    pub funds: Option<Vec<Coin>>,
+    pub unlock: Timestamp,
+}
+
+#[cw_serde]
+pub struct PendingWithdrawalsResp {
+    pub withdrawals: Vec<PendingWithdrawal>,
+}
+
+#[cw_serde]
+pub struct StatusResp {
+    pub paused: bool,
+    pub guardian: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct CampaignResp {
+    pub id: u64,
+    pub target: Coin,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub kind: CampaignKind,
+    pub raised: Uint128,
+    pub refunded: Uint128,
+    pub status: CampaignStatus,
+}
+
+#[cw_serde]
+pub struct CampaignsResp {
+    pub campaigns: Vec<CampaignResp>,
+}
+
+/// Votes are weighted by the donations counted before `start_height`
+#[cw_serde]
+pub struct ProposalResp {
+    pub id: u64,
+    pub proposer: Addr,
+    pub description: String,
+    pub action: ProposalAction,
+    pub start_height: u64,
+    pub expires: Timestamp,
+    pub total_weight: Uint128,
+    pub tally: Tally,
+    pub status: ProposalStatus,
+}
+
+#[cw_serde]
+pub struct ProposalsResp {
+    pub proposals: Vec<ProposalResp>,
+}
+
+#[cw_serde]
+pub struct VoteInfo {
+    pub voter: Addr,
+    pub vote: VoteOption,
+    pub weight: Uint128,
+}
+
+#[cw_serde]
+pub struct VotesResp {
+    pub votes: Vec<VoteInfo>,
+}
+
+/// `liquid` is the bank balance of the contract, including funds reserved for campaigns
+#[cw_serde]
+pub struct StakingResp {
+    pub config: Option<StakingConfig>,
+    pub liquid: Vec<Coin>,
+    pub bonded: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct Hook {
+    pub address: Addr,
+    pub on_error: HookErrorPolicy,
+}
+
+#[cw_serde]
+pub struct HooksResp {
+    pub hooks: Vec<Hook>,
+}
+
+#[cw_serde]
+pub struct MilestonesResp {
+    pub collection: Option<Addr>,
+    pub milestones: Vec<Milestone>,
+}
+
+#[cw_serde]
+pub struct Badge {
+    pub milestone: String,
+    pub token_id: String,
+}
+
+#[cw_serde]
+pub struct BadgesResp {
+    pub badges: Vec<Badge>,
+}
+
+/// `address` is `None` until the reward token instantiation completes, or when the contract
+/// does not reward donations at all
+#[cw_serde]
+pub struct RewardTokenResp {
+    pub address: Option<Addr>,
+    pub rates: Vec<RewardRate>,
+    pub donor_cap: Option<Uint128>,
+    pub total_cap: Option<Uint128>,
+    pub minted: Uint128,
+}
+
 #[cw_serde]
 pub enum ExecMsg {
     Donate {},
+    Receive(Cw20ReceiveMsg),
     Withdraw {},
+    /// Cw20 limits are given in `funds` with the token address as denom
     WithdrawTo {
         recipient: String,
         funds: Option<Vec<Coin>>,
     },
-}
\ No newline at end of file
+    /// Splits the whole contract balance, every native denom and cw20 token, between the
+    /// recipients; shares must sum up to 1, each of them rounded by at most the smallest
+    /// decimal step, and the rounding dust goes to the first recipient
+    WithdrawSplit {
+        shares: Vec<(String, Decimal)>,
+    },
+    /// Queues a withdrawal with the `WithdrawTo` semantics, paid out by `ExecuteWithdraw`
+    /// once the timelock passes. The recipient defaults to the sender.
+    RequestWithdraw {
+        recipient: Option<String>,
+        funds: Option<Vec<Coin>>,
+    },
+    ExecuteWithdraw {
+        id: u64,
+    },
+    CancelWithdraw {
+        id: u64,
+    },
+    ProposeOwner {
+        new_owner: String,
+        expiry: Option<Expiration>,
+    },
+    AcceptOwnership {},
+    CancelOwnershipProposal {},
+    RenounceOwnership {},
+    RetryParentForward {},
+    /// Owner-only; `parents` replaces all the parents (an empty list removes them) and
+    /// restarts their donating countdowns. In governance mode the parents only change through
+    /// `ProposeParents`.
+    UpdateConfig {
+        minimal_donations: Option<Vec<Coin>>,
+        insufficient_donation: Option<InsufficientDonationPolicy>,
+        parents: Option<Vec<Parent>>,
+    },
+    /// Owner or guardian; every other message fails until the owner unpauses
+    Pause {},
+    Unpause {},
+    /// Owner or guardian; sends the whole balance but campaign refunds to the owner, skipping
+    /// the timelock, and works while paused. Not available in governance mode
+    EmergencyWithdraw {},
+    /// Owner-only; campaigns cannot overlap, so donations in the target denom made between
+    /// `start` and `end` go to the single running one
+    OpenCampaign {
+        target: Coin,
+        start: Timestamp,
+        end: Timestamp,
+        kind: CampaignKind,
+    },
+    /// Returns the sender's donations to an all-or-nothing campaign which missed its target
+    ClaimCampaignRefund {
+        id: u64,
+    },
+    /// Mints the reward tokens for the sender's donations to an all-or-nothing campaign which
+    /// met its target; they are held back while the campaign can still be refunded
+    ClaimCampaignReward {
+        id: u64,
+    },
+    /// Owner-only; the hook gets a `DonationHookMsg` on every counted donation
+    AddHook {
+        address: String,
+        on_error: Option<HookErrorPolicy>,
+    },
+    RemoveHook {
+        address: String,
+    },
+    /// Owner-only; replaces the milestone rules. The contract has to be the minter of the
+    /// cw721 `collection`.
+    ConfigureBadges {
+        collection: String,
+        milestones: Vec<Milestone>,
+    },
+    /// Governance mode only; proposes a withdrawal with the same semantics as `WithdrawTo`.
+    /// The sender has to have donated before the current block.
+    Propose {
+        description: String,
+        recipient: String,
+        funds: Option<Vec<Coin>>,
+    },
+    /// Governance mode only; proposes replacing the parents, which the owner can't do alone
+    /// in governance mode. The sender has to have donated before the current block.
+    ProposeParents {
+        description: String,
+        parents: Vec<Parent>,
+    },
+    Vote {
+        id: u64,
+        vote: VoteOption,
+    },
+    /// Pays out a passed proposal; anyone can execute it
+    Execute {
+        id: u64,
+    },
+    /// Marks an expired proposal which did not pass as rejected
+    Close {
+        id: u64,
+    },
+    /// Owner-only; withdrawals pay out liquid funds and undelegate the bonded ones they fall
+    /// short of, which can be withdrawn once they are unbonded
+    Delegate {
+        validator: String,
+        amount: Uint128,
+    },
+    Undelegate {
+        validator: String,
+        amount: Uint128,
+    },
+    /// Owner-only; claims the rewards of every delegation to the contract balance
+    ClaimStakingRewards {},
+}
+
+/// Sent by chain governance, bypassing the owner, the pause and the withdrawal restrictions
+#[cw_serde]
+pub enum SudoMsg {
+    /// Replaces the owner, dropping any pending ownership proposal
+    ForceOwner {
+        owner: String,
+    },
+    ForcePause {},
+    /// Sends the whole liquid balance but campaign refunds, cw20 tokens included, to the
+    /// community pool address
+    ForceWithdraw {
+        community_pool: String,
+    },
+    ResetCounter {},
+}
+
+/// Messages accepted as the payload of `ExecMsg::Receive`
+#[cw_serde]
+pub enum ReceiveMsg {
+    Donate {},
+}
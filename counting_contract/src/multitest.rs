@@ -1,3 +1,7 @@
 pub mod contract;
 #[cfg(test)]
+mod future;
+#[cfg(test)]
+mod subscriber;
+#[cfg(test)]
 mod tests;
\ No newline at end of file
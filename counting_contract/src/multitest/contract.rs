@@ -1,10 +1,18 @@
-use cosmwasm_std::{Addr, Coin, coin, coins, Empty, StdResult};
-use cw_multi_test::{App, BasicApp, Executor};
+use cosmwasm_std::{Addr, Coin, coins, Decimal, StdResult, Timestamp, to_binary, Uint128, Validator};
+use cosmwasm_std::testing::mock_env;
+use cw20::Cw20ExecuteMsg;
+use cw_multi_test::{App, BasicApp, Executor, StakingInfo};
 use cw_multi_test::ContractWrapper;
+use cw_utils::Expiration;
 
-use crate::{execute, instantiate, migrate, query};
+use crate::{execute, instantiate, migrate, query, reply, sudo};
 use crate::error::ContractError;
-use crate::msg::{ExecMsg, InstantiateMsg, Parent, QueryMsg, ValueResp};
+use crate::msg::{
+    BadgesResp, CampaignKind, CampaignResp, CampaignsResp, ConfigResp, DonorResp, DonorsResp, ExecMsg, HookErrorPolicy,
+    HooksResp, InstantiateMsg, InsufficientDonationPolicy, MigrateMsg, Milestone, OwnershipResp, Parent, ParentResp,
+    ParentsResp, ParentStatusResp, PendingWithdrawalsResp, ProposalResp, ProposalsResp, QueryMsg, ReceiveMsg,
+    RewardTokenResp, StakingResp, StatusResp, SudoMsg, ValueAtResp, ValueResp, VoteOption, VotesResp,
+};
 
 pub struct CountingContract(Addr);
 
@@ -13,8 +21,9 @@ impl CountingContract {
         &self.0
     }
 
+    /// Atoms can be delegated to the `validator` validator
     pub fn app_with_funds(sender: impl Into<Option<Addr>>, amount: impl Into<Option<u128>>) -> BasicApp {
-        App::new(|router, _api, storage| {
+        App::new(|router, api, storage| {
             router
                 .bank
                 .init_balance(
@@ -22,11 +31,39 @@ impl CountingContract {
                     &sender.into().unwrap_or_else(|| Addr::unchecked("sender")),
                     coins(amount.into().unwrap_or_else(|| 0), "atom"))
                 .unwrap();
+            router
+                .staking
+                .setup(
+                    storage,
+                    StakingInfo {
+                        bonded_denom: "atom".to_string(),
+                        unbonding_time: 60,
+                        apr: Decimal::percent(10),
+                    },
+                )
+                .unwrap();
+            router
+                .staking
+                .add_validator(
+                    api,
+                    storage,
+                    &mock_env().block,
+                    Validator {
+                        address: "validator".to_string(),
+                        commission: Decimal::zero(),
+                        max_commission: Decimal::one(),
+                        max_change_rate: Decimal::one(),
+                    },
+                )
+                .unwrap();
         })
     }
 
     pub fn store_code(app: &mut App) -> u64 {
-        let contract = ContractWrapper::new(execute, instantiate, query).with_migrate(migrate);
+        let contract = ContractWrapper::new(execute, instantiate, query)
+            .with_reply(reply)
+            .with_sudo(sudo)
+            .with_migrate(migrate);
         app.store_code(Box::new(contract))
     }
 
@@ -39,24 +76,45 @@ impl CountingContract {
         counter: impl Into<Option<u64>>,
         minimal_donation: impl Into<Option<u128>>,
         parent: impl Into<Option<Parent>>,
-    ) -> StdResult<Self> {
+    ) -> Result<Self, ContractError> {
         let sender = sender.into().cloned().unwrap_or_else(|| Addr::unchecked("sender"));
         let counter = Some(counter.into().unwrap_or_default());
         let admin = admin.into().map(Addr::to_string);
-        let minimal_donation = coin(minimal_donation.into().unwrap_or_else(|| 0), "atom");
-        let parent = parent.into();
+        let minimal_donations = minimal_donation
+            .into()
+            .map(|amount| coins(amount, "atom"))
+            .unwrap_or_default();
+        let parents = parent.into().into_iter().collect();
 
-        app.instantiate_contract(
+        Self::instantiate_with_msg(
+            app,
             code_id,
-            sender,
+            &sender,
+            admin,
             &InstantiateMsg {
                 counter,
-                minimal_donation,
-                parent,
+                minimal_donations,
+                parents,
+                ..Default::default()
             },
+        )
+    }
+
+    #[track_caller]
+    pub fn instantiate_with_msg(
+        app: &mut App,
+        code_id: u64,
+        sender: &Addr,
+        admin: impl Into<Option<String>>,
+        msg: &InstantiateMsg,
+    ) -> Result<Self, ContractError> {
+        app.instantiate_contract(
+            code_id,
+            sender.clone(),
+            msg,
             &[],
             "Counting contract",
-            admin,
+            admin.into(),
         )
             .map(CountingContract)
             .map_err(|err| err.downcast().unwrap())
@@ -74,6 +132,25 @@ impl CountingContract {
             .map(|_| ())
     }
 
+    #[track_caller]
+    pub fn donate_tokens(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        token: &Addr,
+        amount: u128,
+    ) -> Result<(), ContractError> {
+        let msg = Cw20ExecuteMsg::Send {
+            contract: self.0.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&ReceiveMsg::Donate {}).unwrap(),
+        };
+
+        app.execute_contract(sender.clone(), token.clone(), &msg, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
     #[track_caller]
     pub fn withdraw(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
         app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Withdraw {}, &[])
@@ -103,6 +180,347 @@ impl CountingContract {
             .map(|_| ())
     }
 
+    #[track_caller]
+    pub fn request_withdraw<'a>(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        recipient: impl Into<Option<&'a Addr>>,
+        funds: impl Into<Option<Vec<Coin>>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::RequestWithdraw {
+                recipient: recipient.into().map(Addr::to_string),
+                funds: funds.into(),
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn execute_withdraw(&self, app: &mut App, sender: &Addr, id: u64) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::ExecuteWithdraw { id }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn cancel_withdraw(&self, app: &mut App, sender: &Addr, id: u64) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::CancelWithdraw { id }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn pause(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Pause {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn unpause(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Unpause {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn emergency_withdraw(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::EmergencyWithdraw {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn open_campaign(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        target: Coin,
+        start: Timestamp,
+        end: Timestamp,
+        kind: CampaignKind,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::OpenCampaign {
+                target,
+                start,
+                end,
+                kind,
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn claim_campaign_refund(&self, app: &mut App, sender: &Addr, id: u64) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::ClaimCampaignRefund { id }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn claim_campaign_reward(&self, app: &mut App, sender: &Addr, id: u64) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::ClaimCampaignReward { id }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn add_hook(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        hook: &Addr,
+        on_error: impl Into<Option<HookErrorPolicy>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::AddHook {
+                address: hook.to_string(),
+                on_error: on_error.into(),
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn remove_hook(&self, app: &mut App, sender: &Addr, hook: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::RemoveHook {
+                address: hook.to_string(),
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn configure_badges(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        collection: &Addr,
+        milestones: Vec<Milestone>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ConfigureBadges {
+                collection: collection.to_string(),
+                milestones,
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn propose(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        description: &str,
+        recipient: &Addr,
+        funds: impl Into<Option<Vec<Coin>>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Propose {
+                description: description.to_string(),
+                recipient: recipient.to_string(),
+                funds: funds.into(),
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn propose_parents(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        description: &str,
+        parents: Vec<Parent>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProposeParents {
+                description: description.to_string(),
+                parents,
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn vote(&self, app: &mut App, sender: &Addr, id: u64, vote: VoteOption) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Vote { id, vote }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn execute_proposal(&self, app: &mut App, sender: &Addr, id: u64) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Execute { id }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn close_proposal(&self, app: &mut App, sender: &Addr, id: u64) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Close { id }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn delegate(&self, app: &mut App, sender: &Addr, validator: &str, amount: u128) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Delegate {
+                validator: validator.to_string(),
+                amount: Uint128::new(amount),
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn undelegate(&self, app: &mut App, sender: &Addr, validator: &str, amount: u128) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Undelegate {
+                validator: validator.to_string(),
+                amount: Uint128::new(amount),
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn claim_staking_rewards(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::ClaimStakingRewards {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn withdraw_split(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        shares: &[(&Addr, Decimal)],
+    ) -> Result<(), ContractError> {
+        let shares = shares
+            .iter()
+            .map(|(recipient, share)| (recipient.to_string(), *share))
+            .collect();
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::WithdrawSplit { shares }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn retry_parent_forward(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::RetryParentForward {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_config(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        minimal_donations: impl Into<Option<Vec<Coin>>>,
+        insufficient_donation: impl Into<Option<InsufficientDonationPolicy>>,
+        parents: impl Into<Option<Vec<Parent>>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateConfig {
+                minimal_donations: minimal_donations.into(),
+                insufficient_donation: insufficient_donation.into(),
+                parents: parents.into(),
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn propose_owner(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        new_owner: &Addr,
+        expiry: impl Into<Option<Expiration>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProposeOwner {
+                new_owner: new_owner.to_string(),
+                expiry: expiry.into(),
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn accept_ownership(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::AcceptOwnership {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn cancel_ownership_proposal(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::CancelOwnershipProposal {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn renounce_ownership(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::RenounceOwnership {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
     #[track_caller]
     pub fn query_value(&self, app: &App) -> StdResult<ValueResp> {
         app.wrap()
@@ -116,8 +534,177 @@ impl CountingContract {
     }
 
     #[track_caller]
-    pub fn migrate(app: &mut App, contract: Addr, code_id: u64, sender: &Addr) -> StdResult<Self> {
-        app.migrate_contract(sender.clone(), contract.clone(), &Empty {}, code_id)
+    pub fn query_donor(&self, app: &App, address: &Addr) -> StdResult<DonorResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Donor { address: address.to_string() })
+    }
+
+    #[track_caller]
+    pub fn query_donors(
+        &self,
+        app: &App,
+        start_after: impl Into<Option<String>>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<DonorsResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Donors {
+                start_after: start_after.into(),
+                limit: limit.into(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_proposal(&self, app: &App, id: u64) -> StdResult<ProposalResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Proposal { id })
+    }
+
+    #[track_caller]
+    pub fn query_proposals(
+        &self,
+        app: &App,
+        start_after: impl Into<Option<u64>>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<ProposalsResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Proposals {
+                start_after: start_after.into(),
+                limit: limit.into(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_votes(
+        &self,
+        app: &App,
+        id: u64,
+        start_after: impl Into<Option<String>>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<VotesResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Votes {
+                id,
+                start_after: start_after.into(),
+                limit: limit.into(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_staking(&self, app: &App) -> StdResult<StakingResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Staking {})
+    }
+
+    #[track_caller]
+    pub fn query_ownership(&self, app: &App) -> StdResult<OwnershipResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Ownership {})
+    }
+
+    #[track_caller]
+    pub fn query_parent(&self, app: &App) -> StdResult<ParentResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Parent {})
+    }
+
+    #[track_caller]
+    pub fn query_parents(&self, app: &App) -> StdResult<ParentsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Parents {})
+    }
+
+    #[track_caller]
+    pub fn query_config(&self, app: &App) -> StdResult<ConfigResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Config {})
+    }
+
+    #[track_caller]
+    pub fn query_parent_status(&self, app: &App) -> StdResult<ParentStatusResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::ParentStatus {})
+    }
+
+    #[track_caller]
+    pub fn query_pending_withdrawals(&self, app: &App) -> StdResult<PendingWithdrawalsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::PendingWithdrawals {})
+    }
+
+    #[track_caller]
+    pub fn query_status(&self, app: &App) -> StdResult<StatusResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Status {})
+    }
+
+    #[track_caller]
+    pub fn query_value_at(&self, app: &App, height: u64) -> StdResult<ValueAtResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::ValueAt { height })
+    }
+
+    #[track_caller]
+    pub fn query_hooks(&self, app: &App) -> StdResult<HooksResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Hooks {})
+    }
+
+    #[track_caller]
+    pub fn query_badges(&self, app: &App, donor: &Addr) -> StdResult<BadgesResp> {
+        app.wrap()
+            .query_wasm_smart(
+                self.0.clone(),
+                &QueryMsg::Badges {
+                    donor: donor.to_string(),
+                },
+            )
+    }
+
+    #[track_caller]
+    pub fn query_reward_token(&self, app: &App) -> StdResult<RewardTokenResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::RewardToken {})
+    }
+
+    #[track_caller]
+    pub fn query_campaign(&self, app: &App, id: u64) -> StdResult<CampaignResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Campaign { id })
+    }
+
+    #[track_caller]
+    pub fn query_campaigns(&self, app: &App) -> StdResult<CampaignsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Campaigns {})
+    }
+
+    #[track_caller]
+    pub fn migrate(app: &mut App, contract: Addr, code_id: u64, sender: &Addr) -> Result<Self, ContractError> {
+        Self::migrate_with_msg(app, contract, code_id, sender, &MigrateMsg::default())
+    }
+
+    #[track_caller]
+    pub fn sudo(&self, app: &mut App, msg: &SudoMsg) -> Result<(), ContractError> {
+        app.wasm_sudo(self.0.clone(), msg)
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn migrate_with_msg(
+        app: &mut App,
+        contract: Addr,
+        code_id: u64,
+        sender: &Addr,
+        msg: &MigrateMsg,
+    ) -> Result<Self, ContractError> {
+        app.migrate_contract(sender.clone(), contract.clone(), msg, code_id)
             .map_err(|err| err.downcast().unwrap())
             .map(|_| Self(contract))
     }
@@ -127,4 +714,10 @@ impl From<CountingContract> for Addr {
     fn from(contract: CountingContract) -> Self {
         contract.0
     }
+}
+
+impl From<Addr> for CountingContract {
+    fn from(addr: Addr) -> Self {
+        Self(addr)
+    }
 }
\ No newline at end of file
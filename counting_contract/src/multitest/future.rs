@@ -0,0 +1,39 @@
+//! Stand-in for a release newer than this contract, which can't be a dependency yet.
+//! It only records its contract version, which is all that refused downgrades care about.
+
+use cosmwasm_std::{Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult, to_binary};
+use cw2::set_contract_version;
+use cw_multi_test::{App, ContractWrapper, Executor};
+
+pub const VERSION: &str = "1.0.0";
+
+fn instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+    set_contract_version(deps.storage, "counting_contract", VERSION)?;
+    Ok(Response::new())
+}
+
+fn execute(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&Empty {})
+}
+
+pub fn store_code(app: &mut App) -> u64 {
+    let contract = ContractWrapper::new(execute, instantiate, query);
+    app.store_code(Box::new(contract))
+}
+
+#[track_caller]
+pub fn instantiate_contract(app: &mut App, code_id: u64, sender: &Addr, admin: &Addr) -> Addr {
+    app.instantiate_contract(
+        code_id,
+        sender.clone(),
+        &Empty {},
+        &[],
+        "Future counting contract",
+        Some(admin.to_string()),
+    )
+        .unwrap()
+}
@@ -0,0 +1,67 @@
+//! Hook contract recording every donation it is notified about, or failing on them.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, to_binary};
+use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+
+use crate::msg::{DonationHookExecuteMsg, DonationHookMsg};
+
+#[cw_serde]
+pub struct SubscriberInstantiateMsg {
+    pub fail: bool,
+}
+
+const FAIL: Item<bool> = Item::new("fail");
+const RECEIVED: Item<Vec<DonationHookMsg>> = Item::new("received");
+
+fn instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, msg: SubscriberInstantiateMsg) -> StdResult<Response> {
+    FAIL.save(deps.storage, &msg.fail)?;
+    RECEIVED.save(deps.storage, &vec![])?;
+
+    Ok(Response::new())
+}
+
+fn execute(deps: DepsMut, _env: Env, _info: MessageInfo, msg: DonationHookExecuteMsg) -> StdResult<Response> {
+    let DonationHookExecuteMsg::DonationHook(msg) = msg;
+
+    if FAIL.load(deps.storage)? {
+        return Err(StdError::generic_err("Subscriber failure"));
+    }
+
+    RECEIVED.update(deps.storage, |mut received| -> StdResult<_> {
+        received.push(msg);
+        Ok(received)
+    })?;
+
+    Ok(Response::new())
+}
+
+fn query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&RECEIVED.load(deps.storage)?)
+}
+
+pub fn store_code(app: &mut App) -> u64 {
+    let contract = ContractWrapper::new(execute, instantiate, query);
+    app.store_code(Box::new(contract))
+}
+
+#[track_caller]
+pub fn instantiate_contract(app: &mut App, code_id: u64, fail: bool) -> Addr {
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked("owner"),
+        &SubscriberInstantiateMsg { fail },
+        &[],
+        "Subscriber",
+        None,
+    )
+        .unwrap()
+}
+
+#[track_caller]
+pub fn received(app: &App, subscriber: &Addr) -> Vec<DonationHookMsg> {
+    app.wrap()
+        .query_wasm_smart(subscriber.clone(), &Empty {})
+        .unwrap()
+}
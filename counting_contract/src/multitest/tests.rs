@@ -1,15 +1,121 @@
-use cosmwasm_std::{Addr, coin, coins, Decimal};
+use cosmwasm_std::{Addr, coin, coins, Decimal, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20QueryMsg};
+use cw721::{Cw721QueryMsg, TokensResponse};
+use cw_utils::Expiration;
 use counting_contract_0_1::multitest::contract::CountingContract as CountingContract_0_1;
-use cw_multi_test::App;
+use counting_contract_0_2::multitest::contract::CountingContract as CountingContract_0_2;
+use counting_contract_0_3::msg::Parent as Parent_0_3;
+use counting_contract_0_3::multitest::contract::CountingContract as CountingContract_0_3;
+use cw_multi_test::{App, ContractWrapper, Executor, StakingSudo, SudoMsg as AppSudoMsg};
 
+use crate::contract::{DONATION_HOOK_REPLY, PARENT_FORWARD_REPLY};
 use crate::error::ContractError;
-use crate::msg::{Parent, ValueResp};
-use crate::state::{STATE, State};
+use crate::msg::{
+    AcceptedToken, Badge, CampaignKind, CampaignStatus, ConfigResp, DonatingPeriodKind, DonationHookMsg, DonorResp,
+    ExecMsg, GovernanceConfig, Hook, HookErrorPolicy, HooksResp, InstantiateMsg, InsufficientDonationPolicy,
+    MigrateMsg, Milestone, MilestoneRule, OwnershipResp, Parent, ParentProgress, ParentResp, ParentsResp,
+    PendingWithdrawal, PendingWithdrawalsResp, ProposalStatus, RewardRate, RewardTokenInit, StakingConfig, StatusResp,
+    SudoMsg, Tally, ValueAtResp, ValueResp, VoteInfo, VoteOption,
+};
+use crate::state::{FORWARDS_IN_FLIGHT, HOOKS_IN_FLIGHT, STATE, State};
 
 use super::contract::CountingContract;
+use super::future;
+use super::subscriber;
 
 const ATOM: &str = "atom";
 
+fn store_cw20(app: &mut App) -> u64 {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    app.store_code(Box::new(contract))
+}
+
+fn instantiate_cw20(app: &mut App, balances: &[(&Addr, u128)]) -> Addr {
+    let code_id = store_cw20(app);
+
+    let initial_balances = balances
+        .iter()
+        .map(|(address, amount)| Cw20Coin {
+            address: address.to_string(),
+            amount: Uint128::new(*amount),
+        })
+        .collect();
+
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked("minter"),
+        &cw20_base::msg::InstantiateMsg {
+            name: "Donation token".to_string(),
+            symbol: "DONT".to_string(),
+            decimals: 6,
+            initial_balances,
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        "Donation token",
+        None,
+    )
+        .unwrap()
+}
+
+fn instantiate_cw721(app: &mut App, minter: &Addr) -> Addr {
+    let contract = ContractWrapper::new(
+        cw721_base::entry::execute,
+        cw721_base::entry::instantiate,
+        cw721_base::entry::query,
+    );
+    let code_id = app.store_code(Box::new(contract));
+
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked("owner"),
+        &cw721_base::InstantiateMsg {
+            name: "Supporter badges".to_string(),
+            symbol: "BADGE".to_string(),
+            minter: minter.to_string(),
+        },
+        &[],
+        "Supporter badges",
+        None,
+    )
+        .unwrap()
+}
+
+fn cw721_tokens(app: &App, collection: &Addr, owner: &Addr) -> Vec<String> {
+    let resp: TokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            collection,
+            &Cw721QueryMsg::Tokens {
+                owner: owner.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    resp.tokens
+}
+
+fn cw20_balance(app: &App, token: &Addr, address: &Addr) -> u128 {
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+
+    resp.balance.u128()
+}
+
 #[test]
 fn instantiate_with_value() {
     let mut app = App::default();
@@ -73,6 +179,67 @@ fn query_increment() {
     assert_eq!(resp, ValueResp { value: 6 });
 }
 
+#[test]
+fn value_at_height() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 18);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+    let instantiated = app.block_info().height;
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.height += 1);
+
+    contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(3, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.height += 1);
+
+    let resp = contract.query_value_at(&app, instantiated - 1).unwrap();
+    assert_eq!(
+        resp,
+        ValueAtResp {
+            height: instantiated - 1,
+            value: None,
+            donations: vec![],
+        }
+    );
+
+    let resp = contract.query_value_at(&app, instantiated).unwrap();
+    assert_eq!(
+        resp,
+        ValueAtResp {
+            height: instantiated,
+            value: Some(1),
+            donations: coins(10, ATOM),
+        }
+    );
+
+    let resp = contract.query_value_at(&app, instantiated + 1).unwrap();
+    assert_eq!(
+        resp,
+        ValueAtResp {
+            height: instantiated + 1,
+            value: Some(3),
+            donations: coins(18, ATOM),
+        }
+    );
+
+    let resp = contract.query_value_at(&app, instantiated + 100).unwrap();
+    assert_eq!(resp.value, Some(3));
+}
+
 #[test]
 fn donate() {
     let mut app = App::default();
@@ -203,6 +370,311 @@ fn withdraw_to() {
     );
 }
 
+#[test]
+fn withdraw_split() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, vec![coin(10, ATOM), coin(101, "osmo")])
+            .unwrap();
+    });
+
+    let token = instantiate_cw20(&mut app, &[(&sender, 20)]);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            accepted_tokens: vec![AcceptedToken {
+                address: token.to_string(),
+                minimal_donation: Uint128::zero(),
+            }],
+            ..Default::default()
+        },
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &[coin(10, ATOM), coin(101, "osmo")])
+        .unwrap();
+    contract
+        .donate_tokens(&mut app, &sender, &token, 20)
+        .unwrap();
+
+    let shares = [
+        (&alice, Decimal::percent(50)),
+        (&bob, Decimal::percent(30)),
+        (&carol, Decimal::percent(20)),
+    ];
+
+    let err = contract
+        .withdraw_split(&mut app, &sender, &shares)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        },
+    );
+
+    let err = contract
+        .withdraw_split(&mut app, &owner, &shares[..2])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidWithdrawShares {
+            total: Decimal::percent(80)
+        },
+    );
+
+    let err = contract
+        .withdraw_split(&mut app, &owner, &[(&alice, Decimal::percent(50)), (&alice, Decimal::percent(50))])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DuplicatedRecipient {
+            address: alice.to_string()
+        },
+    );
+
+    contract
+        .withdraw_split(&mut app, &owner, &shares)
+        .unwrap();
+
+    // The rounding dust of 101 osmo goes to the first recipient
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        vec![coin(5, ATOM), coin(51, "osmo")]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        vec![coin(3, ATOM), coin(30, "osmo")]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&carol).unwrap(),
+        vec![coin(2, ATOM), coin(20, "osmo")]
+    );
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+
+    assert_eq!(cw20_balance(&app, &token, &alice), 10);
+    assert_eq!(cw20_balance(&app, &token, &bob), 6);
+    assert_eq!(cw20_balance(&app, &token, &carol), 4);
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 0);
+}
+
+#[test]
+fn withdraw_even_split() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 10);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    // Thirds only add up to 0.999999999999999999
+    let third = Decimal::from_ratio(1u128, 3u128);
+    contract
+        .withdraw_split(&mut app, &owner, &[(&alice, third), (&bob, third), (&carol, third)])
+        .unwrap();
+
+    assert_eq!(app.wrap().query_all_balances(&alice).unwrap(), coins(4, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&bob).unwrap(), coins(3, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&carol).unwrap(), coins(3, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn timelocked_withdraw() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let receiver = Addr::unchecked("receiver");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 10);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            withdraw_timelock: Some(100),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let err = contract.withdraw(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::WithdrawalTimelocked { timelock: 100 });
+
+    contract
+        .request_withdraw(&mut app, &owner, &receiver, coins(4, ATOM))
+        .unwrap();
+    contract
+        .request_withdraw(&mut app, &owner, None, None)
+        .unwrap();
+
+    let unlock = app.block_info().time.plus_seconds(100);
+    let resp = contract.query_pending_withdrawals(&app).unwrap();
+    assert_eq!(
+        resp,
+        PendingWithdrawalsResp {
+            withdrawals: vec![
+                PendingWithdrawal {
+                    id: 0,
+                    recipient: receiver.clone(),
+                    funds: Some(coins(4, ATOM)),
+                    unlock,
+                },
+                PendingWithdrawal {
+                    id: 1,
+                    recipient: owner.clone(),
+                    funds: None,
+                    unlock,
+                },
+            ],
+        }
+    );
+
+    let err = contract.execute_withdraw(&mut app, &owner, 0).unwrap_err();
+    assert_eq!(err, ContractError::WithdrawalLocked { id: 0, unlock });
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let err = contract.execute_withdraw(&mut app, &sender, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        },
+    );
+
+    contract.execute_withdraw(&mut app, &owner, 0).unwrap();
+    contract.cancel_withdraw(&mut app, &owner, 1).unwrap();
+
+    let err = contract.execute_withdraw(&mut app, &owner, 1).unwrap_err();
+    assert_eq!(err, ContractError::WithdrawalNotFound { id: 1 });
+
+    let resp = contract.query_pending_withdrawals(&app).unwrap();
+    assert_eq!(resp, PendingWithdrawalsResp { withdrawals: vec![] });
+
+    assert_eq!(
+        app.wrap().query_all_balances(receiver).unwrap(),
+        coins(4, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(owner).unwrap(), vec![]);
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(6, ATOM)
+    );
+}
+
+#[test]
+fn pause() {
+    let owner = Addr::unchecked("owner");
+    let guardian = Addr::unchecked("guardian");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 10);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            withdraw_timelock: Some(100),
+            guardian: Some(guardian.to_string()),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap();
+
+    let err = contract.pause(&mut app, &sender).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        },
+    );
+
+    contract.pause(&mut app, &guardian).unwrap();
+
+    let resp = contract.query_status(&app).unwrap();
+    assert_eq!(
+        resp,
+        StatusResp {
+            paused: true,
+            guardian: Some(guardian.clone()),
+        }
+    );
+
+    let err = contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Paused);
+
+    let err = contract
+        .request_withdraw(&mut app, &owner, None, None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::Paused);
+
+    // Only the owner can lift the pause
+    let err = contract.unpause(&mut app, &guardian).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        },
+    );
+
+    // The emergency withdrawal skips both the pause and the timelock
+    contract.emergency_withdraw(&mut app, &guardian).unwrap();
+
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(5, ATOM));
+    assert_eq!(app.wrap().query_all_balances(&guardian).unwrap(), vec![]);
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+
+    contract.unpause(&mut app, &owner).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 2 });
+
+    let resp = contract.query_status(&app).unwrap();
+    assert!(!resp.paused);
+}
+
 #[test]
 fn unauthorized_withdraw() {
     let owner = Addr::unchecked("owner");
@@ -307,11 +779,18 @@ fn migration() {
         state,
         State {
             counter: 1,
-            minimal_donation: coin(10, ATOM),
-            owner: owner.clone(),
-            donating_parent: None,
+            minimal_donations: coins(10, ATOM),
+            owner: Some(owner.clone()),
+            insufficient_donation: InsufficientDonationPolicy::Keep,
         }
     );
+
+    // The history starts with the migration
+    let height = app.block_info().height;
+    let resp = contract.query_value_at(&app, height - 1).unwrap();
+    assert_eq!(resp.value, None);
+    let resp = contract.query_value_at(&app, height).unwrap();
+    assert_eq!(resp.value, Some(1));
 }
 
 #[test]
@@ -355,9 +834,9 @@ fn migration_same_version() {
         state,
         State {
             counter: 1,
-            minimal_donation: coin(10, ATOM),
-            owner: owner.clone(),
-            donating_parent: None,
+            minimal_donations: coins(10, ATOM),
+            owner: Some(owner.clone()),
+            insufficient_donation: InsufficientDonationPolicy::Refund,
         }
     );
 }
@@ -398,6 +877,7 @@ fn donating_parent() {
             addr: parent_contract.addr().to_string(),
             donating_period: 2,
             part: Decimal::percent(10),
+            period_kind: DonatingPeriodKind::Donations,
         },
     )
         .unwrap();
@@ -427,4 +907,2721 @@ fn donating_parent() {
             .unwrap(),
         coins(2, ATOM)
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn donate_below_minimal_refunds() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 5);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        None,
+        10,
+        None,
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 0 });
+
+    assert_eq!(
+        app.wrap().query_all_balances(sender).unwrap(),
+        coins(5, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn donate_below_minimal_rejects() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 5);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: coins(10, ATOM),
+            insufficient_donation: Some(InsufficientDonationPolicy::Reject),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    let err = contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        ContractError::InsufficientDonation {
+            minimal: coins(10, ATOM)
+        },
+    );
+
+    assert_eq!(
+        app.wrap().query_all_balances(sender).unwrap(),
+        coins(5, ATOM)
+    );
+}
+
+#[test]
+fn donate_below_minimal_keeps() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 5);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: coins(10, ATOM),
+            insufficient_donation: Some(InsufficientDonationPolicy::Keep),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 0 });
+
+    assert_eq!(app.wrap().query_all_balances(sender).unwrap(), vec![]);
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(5, ATOM)
+    );
+}
+
+#[test]
+fn donate_multiple_denoms() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, vec![coin(10, ATOM), coin(100, "osmo"), coin(10, "juno")])
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: vec![coin(10, ATOM), coin(50, "osmo")],
+            insufficient_donation: Some(InsufficientDonationPolicy::Reject),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(50, "osmo"))
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &[coin(5, ATOM), coin(50, "osmo")])
+        .unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 2 });
+
+    let err = contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientDonation {
+            minimal: vec![coin(10, ATOM), coin(50, "osmo")]
+        },
+    );
+
+    let err = contract
+        .donate(&mut app, &sender, &[coin(5, ATOM), coin(10, "juno")])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnacceptedDenom {
+            denom: "juno".to_owned()
+        },
+    );
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 2 });
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![coin(5, ATOM), coin(100, "osmo")]
+    );
+}
+
+#[test]
+fn duplicated_minimal_donation_denom() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let err = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: vec![coin(10, ATOM), coin(20, ATOM)],
+            ..Default::default()
+        },
+    ).unwrap_err();
+
+    assert_eq!(
+        err,
+        ContractError::DuplicatedDenom {
+            denom: ATOM.to_owned()
+        },
+    );
+
+    let contract = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, 10, None).unwrap();
+
+    let err = contract
+        .update_config(&mut app, &owner, vec![coin(5, "osmo"), coin(7, "osmo")], None, None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DuplicatedDenom {
+            denom: "osmo".to_owned()
+        },
+    );
+}
+
+#[test]
+fn donor_ledger() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(30, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        None,
+        10,
+        None,
+    ).unwrap();
+
+    let first_block = app.block_info().height;
+
+    contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &bob, &coins(10, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.height += 5);
+
+    contract
+        .donate(&mut app, &alice, &coins(20, ATOM))
+        .unwrap();
+
+    let alice_record = DonorResp {
+        address: alice.clone(),
+        donations: 2,
+        amounts: coins(30, ATOM),
+        first_block,
+        last_block: first_block + 5,
+    };
+    let bob_record = DonorResp {
+        address: bob.clone(),
+        donations: 1,
+        amounts: coins(10, ATOM),
+        first_block,
+        last_block: first_block,
+    };
+
+    let resp = contract.query_donor(&app, &alice).unwrap();
+    assert_eq!(resp, alice_record);
+
+    let carol = Addr::unchecked("carol");
+    let resp = contract.query_donor(&app, &carol).unwrap();
+    assert_eq!(
+        resp,
+        DonorResp {
+            address: carol,
+            donations: 0,
+            amounts: vec![],
+            first_block: 0,
+            last_block: 0,
+        }
+    );
+
+    let resp = contract.query_donors(&app, None, 1).unwrap();
+    assert_eq!(resp.donors, vec![alice_record]);
+
+    let resp = contract
+        .query_donors(&app, alice.to_string(), None)
+        .unwrap();
+    assert_eq!(resp.donors, vec![bob_record]);
+}
+
+#[test]
+fn ownership_transfer() {
+    let owner = Addr::unchecked("owner");
+    let new_owner = Addr::unchecked("new_owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 10);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        None,
+        10,
+        None,
+    ).unwrap();
+
+    contract
+        .propose_owner(&mut app, &owner, &new_owner, None)
+        .unwrap();
+
+    let resp = contract.query_ownership(&app).unwrap();
+    assert_eq!(
+        resp,
+        OwnershipResp {
+            owner: Some(owner.clone()),
+            pending_owner: Some(new_owner.clone()),
+            pending_expiry: None,
+        }
+    );
+
+    let err = contract
+        .accept_ownership(&mut app, &sender)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: new_owner.to_string()
+        },
+    );
+
+    contract
+        .accept_ownership(&mut app, &new_owner)
+        .unwrap();
+
+    let resp = contract.query_ownership(&app).unwrap();
+    assert_eq!(
+        resp,
+        OwnershipResp {
+            owner: Some(new_owner.clone()),
+            pending_owner: None,
+            pending_expiry: None,
+        }
+    );
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let err = contract
+        .withdraw(&mut app, &owner)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: new_owner.to_string()
+        },
+    );
+
+    contract
+        .withdraw(&mut app, &new_owner)
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(new_owner).unwrap(),
+        coins(10, ATOM)
+    );
+}
+
+#[test]
+fn expired_ownership_proposal() {
+    let owner = Addr::unchecked("owner");
+    let new_owner = Addr::unchecked("new_owner");
+
+    let mut app = App::default();
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        None,
+        None,
+        None,
+    ).unwrap();
+
+    let expiry = Expiration::AtHeight(app.block_info().height + 10);
+    contract
+        .propose_owner(&mut app, &owner, &new_owner, expiry)
+        .unwrap();
+
+    app.update_block(|block| block.height += 10);
+
+    let err = contract
+        .accept_ownership(&mut app, &new_owner)
+        .unwrap_err();
+    assert_eq!(err, ContractError::OwnershipProposalExpired);
+}
+
+#[test]
+fn cancel_ownership_proposal() {
+    let owner = Addr::unchecked("owner");
+    let new_owner = Addr::unchecked("new_owner");
+
+    let mut app = App::default();
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        None,
+        None,
+        None,
+    ).unwrap();
+
+    contract
+        .propose_owner(&mut app, &owner, &new_owner, None)
+        .unwrap();
+    contract
+        .cancel_ownership_proposal(&mut app, &owner)
+        .unwrap();
+
+    let err = contract
+        .accept_ownership(&mut app, &new_owner)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoOwnershipProposal);
+}
+
+#[test]
+fn renounce_ownership() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        None,
+        None,
+        None,
+    ).unwrap();
+
+    contract
+        .renounce_ownership(&mut app, &owner)
+        .unwrap();
+
+    let resp = contract.query_ownership(&app).unwrap();
+    assert_eq!(resp.owner, None);
+
+    let err = contract
+        .withdraw(&mut app, &owner)
+        .unwrap_err();
+    assert_eq!(err, ContractError::OwnershipRenounced);
+}
+
+#[test]
+fn donating_multiple_parents() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 20);
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let first_parent = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        None,
+        None,
+    )
+        .unwrap();
+    let second_parent = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        None,
+        None,
+    )
+        .unwrap();
+
+    let parents = vec![
+        Parent {
+            addr: first_parent.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(10),
+            period_kind: DonatingPeriodKind::Donations,
+        },
+        Parent {
+            addr: second_parent.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(20),
+            period_kind: DonatingPeriodKind::Donations,
+        },
+    ];
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: coins(10, ATOM),
+            parents: parents.clone(),
+            ..Default::default()
+        },
+    )
+        .unwrap();
+
+    let resp = contract.query_parents(&app).unwrap();
+    assert_eq!(resp, ParentsResp { parents });
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = first_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 2 });
+
+    let resp = second_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+
+    // Each parent gets its part of the donations since its own previous forward: 10% of the
+    // second donation (1) and 20% of both of them (4)
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(14, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(first_parent.addr()).unwrap(),
+        coins(2, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(second_parent.addr()).unwrap(),
+        coins(4, ATOM)
+    );
+}
+
+#[test]
+fn donating_parents_periodically() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(40, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let seconds_parent = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, None).unwrap();
+    let blocks_parent = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, None).unwrap();
+
+    let start = app.block_info();
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            parents: vec![
+                Parent {
+                    addr: seconds_parent.addr().to_string(),
+                    donating_period: 100,
+                    part: Decimal::percent(10),
+                    period_kind: DonatingPeriodKind::Seconds,
+                },
+                Parent {
+                    addr: blocks_parent.addr().to_string(),
+                    donating_period: 5,
+                    part: Decimal::percent(20),
+                    period_kind: DonatingPeriodKind::Blocks,
+                },
+            ],
+            ..Default::default()
+        },
+    )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(50));
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = seconds_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 0 });
+
+    // The first donation after the deadline forwards 10% of the 30 atom accumulated so far
+    app.update_block(|block| block.time = block.time.plus_seconds(50));
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = seconds_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+    assert_eq!(
+        app.wrap().query_all_balances(seconds_parent.addr()).unwrap(),
+        coins(3, ATOM)
+    );
+
+    // Blocks are counted separately from the time: 20% of all the 40 atom donated
+    app.update_block(|block| block.height += 5);
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = seconds_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+    let resp = blocks_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(29, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(blocks_parent.addr()).unwrap(),
+        coins(8, ATOM)
+    );
+
+    let resp = contract.query_parent_status(&app).unwrap();
+    assert_eq!(
+        resp.parents,
+        vec![
+            ParentProgress {
+                address: seconds_parent.addr().clone(),
+                donating_period: 100,
+                period_kind: DonatingPeriodKind::Seconds,
+                donations_left: 100,
+                next_forward: Some(start.time.plus_seconds(200).seconds()),
+                part: Decimal::percent(10),
+                unforwarded: coins(10, ATOM),
+                unforwarded_tokens: vec![],
+                pending_forward: vec![],
+                pending_tokens: vec![],
+            },
+            ParentProgress {
+                address: blocks_parent.addr().clone(),
+                donating_period: 5,
+                period_kind: DonatingPeriodKind::Blocks,
+                donations_left: 5,
+                next_forward: Some(start.height + 10),
+                part: Decimal::percent(20),
+                unforwarded: vec![],
+                unforwarded_tokens: vec![],
+                pending_forward: vec![],
+                pending_tokens: vec![],
+            },
+        ]
+    );
+}
+
+#[test]
+fn forwarding_only_donations() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &owner, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender, coins(30, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, None).unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        None,
+        Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(50),
+            period_kind: DonatingPeriodKind::Donations,
+        },
+    )
+        .unwrap();
+
+    // Funds sent by the owner are not donations, so none of them is forwarded
+    app.send_tokens(owner.clone(), contract.addr().clone(), &coins(100, ATOM)).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent_contract.addr()).unwrap(),
+        coins(5, ATOM)
+    );
+
+    // The donation already shared is not forwarded again
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent_contract.addr()).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(110, ATOM)
+    );
+
+    contract.withdraw(&mut app, &owner).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent_contract.addr()).unwrap(),
+        coins(15, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(5, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(110, ATOM)
+    );
+}
+
+#[test]
+fn withdrawing_between_forwards() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 20);
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, None).unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        None,
+        Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(50),
+            period_kind: DonatingPeriodKind::Donations,
+        },
+    )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    // Half of the donation is owed to the parent even before it is forwarded
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(5, ATOM)
+    );
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent_contract.addr()).unwrap(),
+        coins(10, ATOM)
+    );
+
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn forwarding_nothing() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 10);
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: coins(1, ATOM),
+            insufficient_donation: Some(InsufficientDonationPolicy::Reject),
+            ..Default::default()
+        },
+    )
+        .unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        None,
+        Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(10),
+            period_kind: DonatingPeriodKind::Donations,
+        },
+    )
+        .unwrap();
+
+    // 10% of 5 atom rounds down to nothing, so the parent is not even called
+    contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap();
+
+    let resp = parent_contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 0 });
+
+    let resp = contract.query_parent_status(&app).unwrap();
+    assert_eq!(resp.parents[0].pending_forward, vec![]);
+
+    // The rounded down donation is shared at the next forward
+    contract
+        .donate(&mut app, &sender, &coins(5, ATOM))
+        .unwrap();
+
+    let resp = parent_contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+    assert_eq!(
+        app.wrap().query_all_balances(parent_contract.addr()).unwrap(),
+        coins(1, ATOM)
+    );
+}
+
+#[test]
+fn invalid_parents() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent = |addr: &str, part| Parent {
+        addr: addr.to_string(),
+        donating_period: 1,
+        part,
+        period_kind: DonatingPeriodKind::Donations,
+    };
+
+    let err = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            parents: vec![
+                parent("first", Decimal::percent(60)),
+                parent("second", Decimal::percent(50)),
+            ],
+            ..Default::default()
+        },
+    )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidParentParts {
+            total: Decimal::percent(110)
+        },
+    );
+
+    // Parts too large to add up are rejected the same way
+    let err = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            parents: vec![
+                parent("first", Decimal::MAX),
+                parent("second", Decimal::percent(10)),
+            ],
+            ..Default::default()
+        },
+    )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidParentParts {
+            total: Decimal::MAX
+        },
+    );
+
+    let err = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            parents: vec![
+                parent("first", Decimal::percent(10)),
+                parent("first", Decimal::percent(10)),
+            ],
+            ..Default::default()
+        },
+    )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DuplicatedParent {
+            address: "first".to_string()
+        },
+    );
+}
+
+#[test]
+fn failed_parent_forward() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 20);
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    // Rejects everything below 2 atom, so single forwards of 10% bounce
+    let parent_contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: coins(2, ATOM),
+            insufficient_donation: Some(InsufficientDonationPolicy::Reject),
+            ..Default::default()
+        },
+    )
+        .unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        10,
+        Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(10),
+            period_kind: DonatingPeriodKind::Donations,
+        },
+    )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 2 });
+
+    let resp = parent_contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 0 });
+
+    let resp = contract.query_parent_status(&app).unwrap();
+    assert_eq!(
+        resp.parents,
+        vec![ParentProgress {
+            address: parent_contract.addr().clone(),
+            donating_period: 1,
+            period_kind: DonatingPeriodKind::Donations,
+            donations_left: 1,
+            next_forward: None,
+            part: Decimal::percent(10),
+            unforwarded: vec![],
+            unforwarded_tokens: vec![],
+            pending_forward: coins(2, ATOM),
+            pending_tokens: vec![],
+        }]
+    );
+
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(20, ATOM)
+    );
+
+    // The failed forwards are owed to the parent, so the owner cannot withdraw them
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(18, ATOM)
+    );
+
+    // Both failed forwards of 1 atom are retried at once
+    contract
+        .retry_parent_forward(&mut app, &sender)
+        .unwrap();
+
+    let resp = parent_contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(parent_contract.addr()).unwrap(),
+        coins(2, ATOM)
+    );
+
+    // Nothing is left in flight once the forwards are done
+    let forward = FORWARDS_IN_FLIGHT
+        .query(&app.wrap(), contract.addr().clone(), PARENT_FORWARD_REPLY << 32)
+        .unwrap();
+    assert_eq!(forward, None);
+
+    let err = contract
+        .retry_parent_forward(&mut app, &sender)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoPendingForwards);
+}
+
+#[test]
+fn query_config() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: coins(10, ATOM),
+            insufficient_donation: Some(InsufficientDonationPolicy::Keep),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    let resp = contract.query_config(&app).unwrap();
+
+    assert_eq!(
+        resp,
+        ConfigResp {
+            owner: Some(owner),
+            minimal_donations: coins(10, ATOM),
+            insufficient_donation: InsufficientDonationPolicy::Keep,
+            accepted_tokens: vec![],
+            withdraw_timelock: None,
+            governance: None,
+            max_parent_depth: 5,
+        }
+    );
+}
+
+#[test]
+fn update_config() {
+    let owner = Addr::unchecked("owner");
+    let member = Addr::unchecked("member");
+
+    let mut app = App::default();
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        None,
+        10,
+        None,
+    ).unwrap();
+
+    let err = contract
+        .update_config(&mut app, &member, coins(5, ATOM), None, None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        },
+    );
+
+    contract
+        .update_config(
+            &mut app,
+            &owner,
+            coins(5, ATOM),
+            InsufficientDonationPolicy::Reject,
+            None,
+        )
+        .unwrap();
+
+    let resp = contract.query_config(&app).unwrap();
+    assert_eq!(
+        resp,
+        ConfigResp {
+            owner: Some(owner),
+            minimal_donations: coins(5, ATOM),
+            insufficient_donation: InsufficientDonationPolicy::Reject,
+            accepted_tokens: vec![],
+            withdraw_timelock: None,
+            governance: None,
+            max_parent_depth: 5,
+        }
+    );
+}
+
+#[test]
+fn update_parents() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 30);
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let old_parent = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        None,
+        None,
+    )
+        .unwrap();
+    let new_parent = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        None,
+        None,
+    )
+        .unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        10,
+        Parent {
+            addr: old_parent.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(10),
+            period_kind: DonatingPeriodKind::Donations,
+        },
+    )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let err = contract
+        .update_config(
+            &mut app,
+            &owner,
+            None,
+            None,
+            vec![Parent {
+                addr: new_parent.addr().to_string(),
+                donating_period: 0,
+                part: Decimal::percent(10),
+                period_kind: DonatingPeriodKind::Donations,
+            }],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidDonatingPeriod {
+            address: new_parent.addr().to_string()
+        },
+    );
+
+    let parents = vec![Parent {
+        addr: new_parent.addr().to_string(),
+        donating_period: 2,
+        part: Decimal::percent(10),
+        period_kind: DonatingPeriodKind::Donations,
+    }];
+    contract
+        .update_config(&mut app, &owner, None, None, parents.clone())
+        .unwrap();
+
+    let resp = contract.query_parents(&app).unwrap();
+    assert_eq!(resp, ParentsResp { parents });
+
+    // The countdown restarted, so the new parent waits for two more donations
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = new_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 0 });
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = new_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+
+    let resp = old_parent.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 0 });
+
+    contract
+        .update_config(&mut app, &owner, None, None, Vec::<Parent>::new())
+        .unwrap();
+
+    let resp = contract.query_parents(&app).unwrap();
+    assert_eq!(resp, ParentsResp { parents: vec![] });
+}
+
+#[test]
+fn update_parents_keeps_unforwarded() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 30);
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let first_parent = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, None).unwrap();
+    let second_parent = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, None).unwrap();
+
+    let parent = |contract: &CountingContract| Parent {
+        addr: contract.addr().to_string(),
+        donating_period: 2,
+        part: Decimal::percent(50),
+        period_kind: DonatingPeriodKind::Donations,
+    };
+
+    let contract = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, parent(&first_parent))
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    contract
+        .update_config(&mut app, &owner, None, None, vec![parent(&first_parent), parent(&second_parent)])
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    // The first parent still gets its part of the donation made before the update
+    assert_eq!(
+        app.wrap().query_all_balances(first_parent.addr()).unwrap(),
+        coins(15, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(second_parent.addr()).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(5, ATOM)
+    );
+}
+
+#[test]
+fn migration_from_0_2() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let old_code_id = CountingContract_0_2::store_code(&mut app);
+    let code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract_0_2::instantiate(&mut app, old_code_id, &owner, &admin, 3, 10).unwrap();
+
+    let contract = CountingContract::migrate(&mut app, contract.into(), code_id, &admin).unwrap();
+
+    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    assert_eq!(
+        state,
+        State {
+            counter: 3,
+            minimal_donations: coins(10, ATOM),
+            owner: Some(owner),
+            insufficient_donation: InsufficientDonationPolicy::Keep,
+        }
+    );
+
+    let resp = contract.query_parents(&app).unwrap();
+    assert_eq!(resp, ParentsResp { parents: vec![] });
+}
+
+#[test]
+fn migration_from_0_3() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let parent = Addr::unchecked("parent");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 30);
+
+    let old_code_id = CountingContract_0_3::store_code(&mut app);
+    let code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract_0_3::instantiate(
+        &mut app,
+        old_code_id,
+        &owner,
+        &admin,
+        None,
+        10,
+        Parent_0_3 {
+            addr: parent.to_string(),
+            donating_period: 5,
+            part: Decimal::percent(10),
+        },
+    )
+        .unwrap();
+
+    for _ in 0..3 {
+        contract
+            .donate(&mut app, &sender, &coins(10, ATOM))
+            .unwrap();
+    }
+
+    let contract = CountingContract::migrate(&mut app, contract.into(), code_id, &admin).unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 3 });
+
+    // Donations below the minimum are still kept, as they were before the migration
+    let resp = contract.query_config(&app).unwrap();
+    assert_eq!(resp.insufficient_donation, InsufficientDonationPolicy::Keep);
+
+    let resp = contract.query_parent_status(&app).unwrap();
+    assert_eq!(
+        resp.parents,
+        vec![ParentProgress {
+            address: parent,
+            donating_period: 5,
+            period_kind: DonatingPeriodKind::Donations,
+            donations_left: 2,
+            next_forward: None,
+            part: Decimal::percent(10),
+            unforwarded: vec![],
+            unforwarded_tokens: vec![],
+            pending_forward: vec![],
+            pending_tokens: vec![],
+        }]
+    );
+}
+
+#[test]
+fn migration_with_config() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let old_code_id = CountingContract_0_3::store_code(&mut app);
+    let code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract_0_3::instantiate(&mut app, old_code_id, &owner, &admin, 3, 10, None).unwrap();
+
+    let contract = CountingContract::migrate_with_msg(
+        &mut app,
+        contract.into(),
+        code_id,
+        &admin,
+        &MigrateMsg {
+            minimal_donations: Some(coins(20, ATOM)),
+            insufficient_donation: Some(InsufficientDonationPolicy::Reject),
+            ..Default::default()
+        },
+    )
+        .unwrap();
+
+    let resp = contract.query_config(&app).unwrap();
+    assert_eq!(
+        resp,
+        ConfigResp {
+            owner: Some(owner),
+            minimal_donations: coins(20, ATOM),
+            insufficient_donation: InsufficientDonationPolicy::Reject,
+            accepted_tokens: vec![],
+            withdraw_timelock: None,
+            governance: None,
+            max_parent_depth: 5,
+        }
+    );
+}
+
+#[test]
+fn migration_downgrade() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let future_code_id = future::store_code(&mut app);
+    let code_id = CountingContract::store_code(&mut app);
+
+    let contract = future::instantiate_contract(&mut app, future_code_id, &owner, &admin);
+
+    let err = CountingContract::migrate(&mut app, contract, code_id, &admin).unwrap_err();
+
+    assert_eq!(
+        err,
+        ContractError::MigrationDowngrade {
+            from: future::VERSION.to_string(),
+            to: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+}
+
+#[test]
+fn donate_tokens() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::default();
+
+    let token = instantiate_cw20(&mut app, &[(&sender, 30)]);
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            minimal_donations: coins(10, ATOM),
+            accepted_tokens: vec![AcceptedToken {
+                address: token.to_string(),
+                minimal_donation: Uint128::new(10),
+            }],
+            ..Default::default()
+        },
+    )
+        .unwrap();
+
+    contract
+        .donate_tokens(&mut app, &sender, &token, 10)
+        .unwrap();
+
+    // Below the token minimum, so it gets refunded
+    contract
+        .donate_tokens(&mut app, &sender, &token, 5)
+        .unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+
+    let resp = contract.query_donor(&app, &sender).unwrap();
+    assert_eq!(resp.amounts, coins(10, token.as_str()));
+
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 10);
+    assert_eq!(cw20_balance(&app, &token, &sender), 20);
+
+    contract
+        .withdraw(&mut app, &owner)
+        .unwrap();
+
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 0);
+    assert_eq!(cw20_balance(&app, &token, &owner), 10);
+}
+
+#[test]
+fn donate_unaccepted_tokens() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::default();
+
+    let token = instantiate_cw20(&mut app, &[(&sender, 10)]);
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        None,
+        None,
+        None,
+    )
+        .unwrap();
+
+    let err = contract
+        .donate_tokens(&mut app, &sender, &token, 10)
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        ContractError::UnacceptedToken {
+            token: token.to_string()
+        }
+    );
+}
+
+#[test]
+fn donating_tokens_to_parent() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::default();
+
+    let token = instantiate_cw20(&mut app, &[(&sender, 20)]);
+    let accepted_tokens = vec![AcceptedToken {
+        address: token.to_string(),
+        minimal_donation: Uint128::zero(),
+    }];
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            accepted_tokens: accepted_tokens.clone(),
+            ..Default::default()
+        },
+    )
+        .unwrap();
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            accepted_tokens,
+            parents: vec![Parent {
+                addr: parent_contract.addr().to_string(),
+                donating_period: 2,
+                part: Decimal::percent(10),
+                period_kind: DonatingPeriodKind::Donations,
+            }],
+            ..Default::default()
+        },
+    )
+        .unwrap();
+
+    contract
+        .donate_tokens(&mut app, &sender, &token, 10)
+        .unwrap();
+    contract
+        .donate_tokens(&mut app, &sender, &token, 10)
+        .unwrap();
+
+    let resp = parent_contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 18);
+    assert_eq!(cw20_balance(&app, &token, parent_contract.addr()), 2);
+}
+
+#[test]
+fn all_or_nothing_campaign() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(30, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let parent = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        None,
+        None,
+        Parent {
+            addr: parent.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(10),
+            period_kind: DonatingPeriodKind::Donations,
+        },
+    )
+        .unwrap();
+
+    let now = app.block_info().time;
+    let start = now.plus_seconds(10);
+    let end = now.plus_seconds(100);
+
+    let err = contract
+        .open_campaign(&mut app, &owner, coin(100, ATOM), end, start, CampaignKind::AllOrNothing)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidCampaignPeriod);
+
+    contract
+        .open_campaign(&mut app, &owner, coin(100, ATOM), start, end, CampaignKind::AllOrNothing)
+        .unwrap();
+
+    let err = contract
+        .open_campaign(
+            &mut app,
+            &owner,
+            coin(50, ATOM),
+            now.plus_seconds(50),
+            now.plus_seconds(200),
+            CampaignKind::KeepItAll,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::CampaignOverlap { id: 0 });
+
+    // Donations before the campaign starts are not credited to it
+    contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(10));
+
+    // The parent is paid its part of alice's campaign donation before the campaign ends
+    contract
+        .donate(&mut app, &alice, &coins(20, ATOM))
+        .unwrap();
+    assert_eq!(app.wrap().query_all_balances(parent.addr()).unwrap(), coins(3, ATOM));
+
+    contract
+        .donate(&mut app, &bob, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = contract.query_campaign(&app, 0).unwrap();
+    assert_eq!(resp.raised.u128(), 30);
+    assert_eq!(resp.status, CampaignStatus::Active);
+
+    let err = contract.claim_campaign_refund(&mut app, &alice, 0).unwrap_err();
+    assert_eq!(err, ContractError::CampaignNotRefundable { id: 0 });
+
+    // Funds raised by a running campaign stay in the contract, as does the parent's part of bob's donation
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(6, ATOM));
+
+    // Neither the emergency nor the chain governance withdrawal takes them
+    contract.pause(&mut app, &owner).unwrap();
+    contract.emergency_withdraw(&mut app, &owner).unwrap();
+    contract.unpause(&mut app, &owner).unwrap();
+    contract
+        .sudo(&mut app, &SudoMsg::ForceWithdraw { community_pool: owner.to_string() })
+        .unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(6, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), coins(31, ATOM));
+
+    app.update_block(|block| block.time = block.time.plus_seconds(90));
+
+    let resp = contract.query_campaign(&app, 0).unwrap();
+    assert_eq!(resp.status, CampaignStatus::Failed);
+
+    contract.claim_campaign_refund(&mut app, &alice, 0).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&alice).unwrap(), coins(20, ATOM));
+
+    let err = contract.claim_campaign_refund(&mut app, &alice, 0).unwrap_err();
+    assert_eq!(err, ContractError::NoCampaignDonation { id: 0 });
+
+    let resp = contract.query_campaigns(&app).unwrap();
+    assert_eq!(resp.campaigns.len(), 1);
+    assert_eq!(resp.campaigns[0].refunded.u128(), 20);
+
+    // Only the donation made before the campaign still counts for alice
+    let resp = contract.query_donor(&app, &alice).unwrap();
+    assert_eq!(resp.amounts, coins(10, ATOM));
+
+    // The parent was already paid for alice's donation, so only bob's is left in its pool
+    let resp = contract.query_parent_status(&app).unwrap();
+    assert_eq!(resp.parents[0].unforwarded, coins(10, ATOM));
+
+    // Bob's donation is still reserved for his refund
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(6, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), coins(11, ATOM));
+
+    contract.claim_campaign_refund(&mut app, &bob, 0).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&bob).unwrap(), coins(10, ATOM));
+
+    let resp = contract.query_donor(&app, &bob).unwrap();
+    assert_eq!(resp.amounts, vec![]);
+
+    let resp = contract.query_parent_status(&app).unwrap();
+    assert_eq!(resp.parents[0].unforwarded, vec![]);
+
+    // Once the parent is owed nothing of the refunded donations, the rest is withdrawable
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(7, ATOM));
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn keep_it_all_campaign() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 20);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+
+    let now = app.block_info().time;
+    contract
+        .open_campaign(&mut app, &owner, coin(100, ATOM), now, now.plus_seconds(100), CampaignKind::KeepItAll)
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(20, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let resp = contract.query_campaign(&app, 0).unwrap();
+    assert_eq!(resp.raised.u128(), 20);
+    assert_eq!(resp.status, CampaignStatus::Failed);
+
+    let err = contract.claim_campaign_refund(&mut app, &sender, 0).unwrap_err();
+    assert_eq!(err, ContractError::CampaignNotRefundable { id: 0 });
+
+    contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), coins(20, ATOM));
+}
+
+#[test]
+fn donation_hooks() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 20);
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let subscriber_id = subscriber::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+    let subscriber = subscriber::instantiate_contract(&mut app, subscriber_id, false);
+    let failing = subscriber::instantiate_contract(&mut app, subscriber_id, true);
+
+    let err = contract
+        .add_hook(&mut app, &sender, &subscriber, None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        },
+    );
+
+    contract
+        .add_hook(&mut app, &owner, &subscriber, None)
+        .unwrap();
+    contract
+        .add_hook(&mut app, &owner, &failing, HookErrorPolicy::Remove)
+        .unwrap();
+
+    let err = contract
+        .add_hook(&mut app, &owner, &subscriber, None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HookAlreadyRegistered {
+            address: subscriber.to_string()
+        },
+    );
+
+    // The failing hook is removed, but the donation goes through
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        subscriber::received(&app, &subscriber),
+        vec![DonationHookMsg {
+            donor: sender.clone(),
+            funds: coins(10, ATOM),
+            counter: 1,
+        }]
+    );
+
+    let resp = contract.query_hooks(&app).unwrap();
+    assert_eq!(
+        resp,
+        HooksResp {
+            hooks: vec![Hook {
+                address: subscriber.clone(),
+                on_error: HookErrorPolicy::Ignore,
+            }],
+        }
+    );
+
+    // Neither the succeeded nor the failed hook is left in flight
+    for index in 0..2 {
+        let hook = HOOKS_IN_FLIGHT
+            .query(&app.wrap(), contract.addr().clone(), (DONATION_HOOK_REPLY << 32) | index)
+            .unwrap();
+        assert_eq!(hook, None);
+    }
+
+    contract
+        .remove_hook(&mut app, &owner, &subscriber)
+        .unwrap();
+
+    let err = contract
+        .remove_hook(&mut app, &owner, &subscriber)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HookNotRegistered {
+            address: subscriber.to_string()
+        },
+    );
+
+    contract
+        .add_hook(&mut app, &owner, &failing, HookErrorPolicy::Revert)
+        .unwrap();
+
+    // The subscriber error is not a `ContractError`, so it is not downcast by the helper
+    app.execute_contract(sender.clone(), contract.addr().clone(), &ExecMsg::Donate {}, &coins(10, ATOM))
+        .unwrap_err();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp, ValueResp { value: 1 });
+    assert_eq!(subscriber::received(&app, &subscriber).len(), 1);
+}
+
+#[test]
+fn milestone_badges() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(30, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+    let collection = instantiate_cw721(&mut app, contract.addr());
+
+    let milestone = |id: &str, rule| Milestone {
+        id: id.to_string(),
+        rule,
+        token_uri: None,
+    };
+
+    let err = contract
+        .configure_badges(
+            &mut app,
+            &owner,
+            &collection,
+            vec![milestone("first", MilestoneRule::Donations { count: 0 })],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidMilestone {
+            id: "first".to_string()
+        },
+    );
+
+    let err = contract
+        .configure_badges(
+            &mut app,
+            &owner,
+            &collection,
+            vec![milestone(
+                "top",
+                MilestoneRule::TopDonors {
+                    denom: ATOM.to_string(),
+                    rank: 21,
+                },
+            )],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::TopDonorsRankTooHigh {
+            id: "top".to_string(),
+            max: 20,
+        },
+    );
+
+    contract
+        .configure_badges(
+            &mut app,
+            &owner,
+            &collection,
+            vec![
+                milestone("first", MilestoneRule::Donations { count: 1 }),
+                milestone("third", MilestoneRule::Donations { count: 3 }),
+                milestone("big", MilestoneRule::Amount { amount: coin(20, ATOM) }),
+                milestone(
+                    "top",
+                    MilestoneRule::TopDonors {
+                        denom: ATOM.to_string(),
+                        rank: 1,
+                    },
+                ),
+            ],
+        )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &bob, &coins(5, ATOM))
+        .unwrap();
+
+    assert_eq!(cw721_tokens(&app, &collection, &alice), vec!["first/alice", "top/alice"]);
+    assert_eq!(cw721_tokens(&app, &collection, &bob), vec!["first/bob"]);
+
+    // Every badge is minted only once
+    contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        cw721_tokens(&app, &collection, &alice),
+        vec!["big/alice", "first/alice", "third/alice", "top/alice"]
+    );
+
+    let resp = contract.query_badges(&app, &bob).unwrap();
+    assert_eq!(
+        resp.badges,
+        vec![Badge {
+            milestone: "first".to_string(),
+            token_id: "first/bob".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn reward_tokens() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, vec![coin(100, ATOM), coin(100, "osmo")])
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let cw20_id = store_cw20(&mut app);
+
+    let reward_token = RewardTokenInit {
+        code_id: cw20_id,
+        name: "Counting governance".to_string(),
+        symbol: "CGOV".to_string(),
+        decimals: 6,
+        rates: vec![
+            RewardRate {
+                denom: ATOM.to_string(),
+                rate: Decimal::percent(200),
+            },
+            RewardRate {
+                denom: "osmo".to_string(),
+                rate: Decimal::percent(50),
+            },
+        ],
+        donor_cap: Some(Uint128::new(50)),
+        total_cap: Some(Uint128::new(70)),
+    };
+
+    let err = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            reward_token: Some(RewardTokenInit {
+                rates: vec![reward_token.rates[0].clone(), reward_token.rates[0].clone()],
+                ..reward_token.clone()
+            }),
+            ..Default::default()
+        },
+    )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DuplicatedDenom {
+            denom: ATOM.to_string()
+        },
+    );
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            reward_token: Some(reward_token),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    let token = contract.query_reward_token(&app).unwrap().address.unwrap();
+
+    contract
+        .donate(&mut app, &alice, &[coin(10, ATOM), coin(10, "osmo")])
+        .unwrap();
+    assert_eq!(cw20_balance(&app, &token, &alice), 25);
+
+    // Alice reaches the donor cap
+    contract
+        .donate(&mut app, &alice, &coins(20, ATOM))
+        .unwrap();
+    assert_eq!(cw20_balance(&app, &token, &alice), 50);
+
+    contract
+        .donate(&mut app, &alice, &coins(20, ATOM))
+        .unwrap();
+    assert_eq!(cw20_balance(&app, &token, &alice), 50);
+
+    // Bob gets whatever is left below the total cap
+    contract
+        .donate(&mut app, &bob, &coins(20, ATOM))
+        .unwrap();
+    assert_eq!(cw20_balance(&app, &token, &bob), 20);
+
+    let resp = contract.query_reward_token(&app).unwrap();
+    assert_eq!(resp.minted, Uint128::new(70));
+    assert_eq!(resp.total_cap, Some(Uint128::new(70)));
+}
+
+#[test]
+fn campaign_rewards() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(30, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(60, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let cw20_id = store_cw20(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            reward_token: Some(RewardTokenInit {
+                code_id: cw20_id,
+                name: "Counting governance".to_string(),
+                symbol: "CGOV".to_string(),
+                decimals: 6,
+                rates: vec![RewardRate {
+                    denom: ATOM.to_string(),
+                    rate: Decimal::one(),
+                }],
+                donor_cap: None,
+                total_cap: None,
+            }),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    let token = contract.query_reward_token(&app).unwrap().address.unwrap();
+
+    let now = app.block_info().time;
+    contract
+        .open_campaign(&mut app, &owner, coin(50, ATOM), now, now.plus_seconds(100), CampaignKind::AllOrNothing)
+        .unwrap();
+
+    // Nothing is minted while the donation can still be refunded
+    contract
+        .donate(&mut app, &alice, &coins(20, ATOM))
+        .unwrap();
+    assert_eq!(cw20_balance(&app, &token, &alice), 0);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let err = contract.claim_campaign_reward(&mut app, &alice, 0).unwrap_err();
+    assert_eq!(err, ContractError::CampaignNotRewardable { id: 0 });
+
+    contract.claim_campaign_refund(&mut app, &alice, 0).unwrap();
+    assert_eq!(app.wrap().query_all_balances(&alice).unwrap(), coins(30, ATOM));
+    assert_eq!(cw20_balance(&app, &token, &alice), 0);
+
+    let now = app.block_info().time;
+    contract
+        .open_campaign(&mut app, &owner, coin(50, ATOM), now, now.plus_seconds(100), CampaignKind::AllOrNothing)
+        .unwrap();
+
+    contract
+        .donate(&mut app, &bob, &coins(60, ATOM))
+        .unwrap();
+
+    let err = contract.claim_campaign_reward(&mut app, &bob, 1).unwrap_err();
+    assert_eq!(err, ContractError::CampaignNotRewardable { id: 1 });
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    // Donations outside of campaigns are rewarded right away
+    contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(cw20_balance(&app, &token, &alice), 10);
+
+    contract.claim_campaign_reward(&mut app, &bob, 1).unwrap();
+    assert_eq!(cw20_balance(&app, &token, &bob), 60);
+
+    let err = contract.claim_campaign_reward(&mut app, &bob, 1).unwrap_err();
+    assert_eq!(err, ContractError::NoCampaignDonation { id: 1 });
+}
+
+#[test]
+fn governed_withdrawals() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let charity = Addr::unchecked("charity");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(30, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(10, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &carol, coins(10, "osmo"))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            governance: Some(GovernanceConfig {
+                denom: ATOM.to_string(),
+                quorum: Decimal::percent(50),
+                threshold: Decimal::percent(50),
+                voting_period: 100,
+            }),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &alice, &coins(30, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &bob, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &carol, &coins(10, "osmo"))
+        .unwrap();
+
+    let err = contract.withdraw(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::WithdrawalsGoverned);
+
+    // Pausing does not let the owner drain the contract either
+    contract.pause(&mut app, &owner).unwrap();
+    let err = contract.emergency_withdraw(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::WithdrawalsGoverned);
+    contract.unpause(&mut app, &owner).unwrap();
+
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), vec![]);
+
+    // Donations count as voting weight from the next block
+    let err = contract
+        .propose(&mut app, &alice, "To charity", &charity, None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoVotingWeight);
+
+    app.update_block(|block| block.height += 1);
+
+    let err = contract
+        .propose(&mut app, &carol, "To charity", &charity, None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoVotingWeight);
+
+    contract
+        .propose(&mut app, &alice, "To charity", &charity, None)
+        .unwrap();
+
+    contract
+        .vote(&mut app, &bob, 0, VoteOption::No)
+        .unwrap();
+    let err = contract
+        .vote(&mut app, &bob, 0, VoteOption::Yes)
+        .unwrap_err();
+    assert_eq!(err, ContractError::AlreadyVoted { id: 0 });
+
+    let err = contract.execute_proposal(&mut app, &bob, 0).unwrap_err();
+    assert_eq!(err, ContractError::ProposalNotPassed { id: 0 });
+
+    contract
+        .vote(&mut app, &alice, 0, VoteOption::Yes)
+        .unwrap();
+
+    let resp = contract.query_proposal(&app, 0).unwrap();
+    assert_eq!(resp.total_weight, Uint128::new(40));
+    assert_eq!(
+        resp.tally,
+        Tally {
+            yes: Uint128::new(30),
+            no: Uint128::new(10),
+            abstain: Uint128::zero(),
+        }
+    );
+    assert_eq!(resp.status, ProposalStatus::Passed);
+
+    let resp = contract.query_votes(&app, 0, None, None).unwrap();
+    assert_eq!(
+        resp.votes,
+        vec![
+            VoteInfo {
+                voter: alice.clone(),
+                vote: VoteOption::Yes,
+                weight: Uint128::new(30),
+            },
+            VoteInfo {
+                voter: bob.clone(),
+                vote: VoteOption::No,
+                weight: Uint128::new(10),
+            },
+        ]
+    );
+
+    contract.execute_proposal(&mut app, &carol, 0).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&charity).unwrap(),
+        vec![coin(40, ATOM), coin(10, "osmo")]
+    );
+    assert_eq!(contract.query_proposal(&app, 0).unwrap().status, ProposalStatus::Executed);
+
+    let err = contract.execute_proposal(&mut app, &carol, 0).unwrap_err();
+    assert_eq!(err, ContractError::ProposalNotPassed { id: 0 });
+
+    // Without the quorum the proposal is rejected once it expires
+    contract
+        .propose(&mut app, &bob, "To bob", &bob, None)
+        .unwrap();
+    contract
+        .vote(&mut app, &bob, 1, VoteOption::Yes)
+        .unwrap();
+
+    let err = contract.close_proposal(&mut app, &bob, 1).unwrap_err();
+    assert_eq!(err, ContractError::ProposalNotClosable { id: 1 });
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let err = contract
+        .vote(&mut app, &alice, 1, VoteOption::Yes)
+        .unwrap_err();
+    assert_eq!(err, ContractError::ProposalNotOpen { id: 1 });
+
+    contract.close_proposal(&mut app, &bob, 1).unwrap();
+    let err = contract.close_proposal(&mut app, &bob, 1).unwrap_err();
+    assert_eq!(err, ContractError::ProposalNotClosable { id: 1 });
+
+    let resp = contract.query_proposals(&app, None, None).unwrap();
+    let statuses: Vec<_> = resp.proposals.into_iter().map(|p| p.status).collect();
+    assert_eq!(statuses, vec![ProposalStatus::Executed, ProposalStatus::Rejected]);
+}
+
+#[test]
+fn governed_parents() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+
+    let mut app = CountingContract::app_with_funds(alice.clone(), 20);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let owned_parent = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+    let charity = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            governance: Some(GovernanceConfig {
+                denom: ATOM.to_string(),
+                quorum: Decimal::percent(50),
+                threshold: Decimal::percent(50),
+                voting_period: 100,
+            }),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    let parent = |contract: &CountingContract| Parent {
+        addr: contract.addr().to_string(),
+        donating_period: 1,
+        part: Decimal::one(),
+        period_kind: DonatingPeriodKind::Donations,
+    };
+
+    // The owner can't redirect the donations to a parent of their own
+    let err = contract
+        .update_config(&mut app, &owner, None, None, vec![parent(&owned_parent)])
+        .unwrap_err();
+    assert_eq!(err, ContractError::ParentsGoverned);
+
+    contract
+        .update_config(&mut app, &owner, coins(5, ATOM), None, None)
+        .unwrap();
+
+    contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.height += 1);
+
+    contract
+        .propose_parents(&mut app, &alice, "To charity", vec![parent(&charity)])
+        .unwrap();
+    contract
+        .vote(&mut app, &alice, 0, VoteOption::Yes)
+        .unwrap();
+    contract.execute_proposal(&mut app, &owner, 0).unwrap();
+
+    let resp = contract.query_parents(&app).unwrap();
+    assert_eq!(resp, ParentsResp { parents: vec![parent(&charity)] });
+
+    contract
+        .donate(&mut app, &alice, &coins(10, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(charity.addr()).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(owned_parent.addr()).unwrap(), vec![]);
+}
+
+#[test]
+fn refunded_voting_weight() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(20, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            governance: Some(GovernanceConfig {
+                denom: ATOM.to_string(),
+                quorum: Decimal::percent(50),
+                threshold: Decimal::percent(50),
+                voting_period: 100,
+            }),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &bob, &coins(10, ATOM))
+        .unwrap();
+
+    let now = app.block_info().time;
+    contract
+        .open_campaign(&mut app, &owner, coin(50, ATOM), now, now.plus_seconds(100), CampaignKind::AllOrNothing)
+        .unwrap();
+    contract
+        .donate(&mut app, &alice, &coins(20, ATOM))
+        .unwrap();
+
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(100);
+        block.height += 1;
+    });
+
+    contract.claim_campaign_refund(&mut app, &alice, 0).unwrap();
+    app.update_block(|block| block.height += 1);
+
+    // Alice got her donation back, so she has no say and Bob's weight is the whole total
+    let err = contract
+        .propose(&mut app, &alice, "To Alice", &alice, None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoVotingWeight);
+
+    contract
+        .propose(&mut app, &bob, "To Bob", &bob, None)
+        .unwrap();
+
+    let resp = contract.query_proposal(&app, 0).unwrap();
+    assert_eq!(resp.total_weight, Uint128::new(10));
+}
+
+#[test]
+fn stake_donations() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 1000);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        contract_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            staking: Some(StakingConfig {
+                denom: ATOM.to_string(),
+                max_part: Decimal::percent(50),
+            }),
+            ..Default::default()
+        },
+    ).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(1000, ATOM))
+        .unwrap();
+
+    let err = contract
+        .delegate(&mut app, &sender, "validator", 400)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        },
+    );
+
+    let err = contract
+        .delegate(&mut app, &owner, "validator", 600)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::StakingLimitExceeded {
+            available: Uint128::new(500)
+        },
+    );
+
+    contract
+        .delegate(&mut app, &owner, "validator", 400)
+        .unwrap();
+
+    let resp = contract.query_staking(&app).unwrap();
+    assert_eq!(resp.liquid, coins(600, ATOM));
+    assert_eq!(resp.bonded, coins(400, ATOM));
+
+    // Bonded funds count towards the limit
+    let err = contract
+        .delegate(&mut app, &owner, "validator", 200)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::StakingLimitExceeded {
+            available: Uint128::new(100)
+        },
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(365 * 24 * 60 * 60));
+
+    contract.claim_staking_rewards(&mut app, &owner).unwrap();
+
+    // A year of 10% on the 400 atom bonded
+    let resp = contract.query_staking(&app).unwrap();
+    assert_eq!(resp.liquid, coins(640, ATOM));
+
+    // Only the liquid funds are paid out, the bonded ones they fall short of get undelegated
+    contract
+        .withdraw_to(&mut app, &owner, &owner, coins(700, ATOM))
+        .unwrap();
+
+    let resp = contract.query_staking(&app).unwrap();
+    assert_eq!(resp.bonded, coins(340, ATOM));
+
+    contract
+        .undelegate(&mut app, &owner, "validator", 100)
+        .unwrap();
+
+    // A full withdrawal undelegates everything still bonded
+    contract.withdraw(&mut app, &owner).unwrap();
+
+    let resp = contract.query_staking(&app).unwrap();
+    assert_eq!(resp.liquid, vec![]);
+    assert_eq!(resp.bonded, vec![]);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(60));
+    app.sudo(AppSudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+
+    let resp = contract.query_staking(&app).unwrap();
+    assert_eq!(resp.liquid, coins(400, ATOM));
+    assert_eq!(resp.bonded, vec![]);
+}
+
+#[test]
+fn sudo_administration() {
+    let owner = Addr::unchecked("owner");
+    let new_owner = Addr::unchecked("new_owner");
+    let sender = Addr::unchecked("sender");
+    let community_pool = Addr::unchecked("community_pool");
+
+    let mut app = CountingContract::app_with_funds(sender.clone(), 10);
+
+    let contract_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(&mut app, contract_id, &owner, None, None, None, None).unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    contract
+        .sudo(
+            &mut app,
+            &SudoMsg::ForceOwner {
+                owner: new_owner.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(contract.query_ownership(&app).unwrap().owner, Some(new_owner.clone()));
+
+    contract
+        .sudo(&mut app, &SudoMsg::ForcePause {})
+        .unwrap();
+    let err = contract
+        .donate(&mut app, &sender, &[])
+        .unwrap_err();
+    assert_eq!(err, ContractError::Paused);
+
+    // Works while paused
+    contract
+        .sudo(
+            &mut app,
+            &SudoMsg::ForceWithdraw {
+                community_pool: community_pool.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(app.wrap().query_all_balances(&community_pool).unwrap(), coins(10, ATOM));
+
+    contract.unpause(&mut app, &new_owner).unwrap();
+
+    contract
+        .sudo(&mut app, &SudoMsg::ResetCounter {})
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap(), ValueResp { value: 0 });
+}
+
+#[test]
+fn parent_chain() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent = |contract: &CountingContract| Parent {
+        addr: contract.addr().to_string(),
+        donating_period: 1,
+        part: Decimal::percent(10),
+        period_kind: DonatingPeriodKind::Donations,
+    };
+
+    let root = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, None).unwrap();
+    let middle = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, parent(&root)).unwrap();
+    let leaf = CountingContract::instantiate(&mut app, code_id, &owner, None, None, None, parent(&middle)).unwrap();
+
+    let resp = leaf.query_parent(&app).unwrap();
+    assert_eq!(
+        resp,
+        ParentResp {
+            parents: vec![middle.addr().clone()],
+            depth: 2,
+        }
+    );
+
+    let err = root
+        .update_config(&mut app, &owner, None, None, vec![parent(&leaf)])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ParentCycle {
+            address: leaf.addr().to_string()
+        },
+    );
+
+    let err = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        None,
+        &InstantiateMsg {
+            parents: vec![parent(&leaf)],
+            max_parent_depth: Some(2),
+            ..Default::default()
+        },
+    )
+        .unwrap_err();
+    assert_eq!(err, ContractError::ParentChainTooDeep { max_depth: 2 });
+}
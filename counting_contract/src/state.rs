@@ -1,13 +1,20 @@
-use cosmwasm_std::{Addr, Coin, Decimal};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Coin, Decimal, Timestamp, Uint128};
+use cw20::Cw20CoinVerified;
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+use cw_utils::Expiration;
 use serde::{Deserialize, Serialize};
 
+use crate::msg::{
+    CampaignKind, DonatingPeriodKind, GovernanceConfig, HookErrorPolicy, InsufficientDonationPolicy, Milestone,
+    ProposalAction, ProposalStatus, RewardRate, StakingConfig, Tally, VoteOption,
+};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct State {
     pub counter: u64,
-    pub minimal_donation: Coin,
-    pub owner: Addr,
-    pub donating_parent: Option<u64>,
+    pub minimal_donations: Vec<Coin>,
+    pub owner: Option<Addr>,
+    pub insufficient_donation: InsufficientDonationPolicy,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -15,7 +22,150 @@ pub struct ParentDonation {
     pub address: Addr,
     pub donating_parent_period: u64,
     pub part: Decimal,
+    pub donations_left: u64,
+    /// Contracts a forwarded donation can reach through this parent, `0` for parents migrated
+    /// from 0.3.0 which were never checked
+    pub depth: u32,
+    pub period_kind: DonatingPeriodKind,
+    /// Time in seconds or block height of the previous forward, unused when counting donations
+    pub last_forward: u64,
+    /// Donations since the previous forward, the parent gets its `part` of them
+    pub unforwarded: Vec<Coin>,
+    pub unforwarded_tokens: Vec<Cw20CoinVerified>,
+    /// Counter value of the donation which made the previous forward, the donations counted
+    /// after it are still in `unforwarded`
+    pub forwarded_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DonorRecord {
+    pub donations: u64,
+    pub amounts: Vec<Coin>,
+    pub first_block: u64,
+    pub last_block: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PendingOwner {
+    pub new_owner: Addr,
+    pub expiry: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ParentForward {
+    pub parent: Addr,
+    pub funds: Vec<Coin>,
+    pub tokens: Vec<Cw20CoinVerified>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct PendingForward {
+    pub funds: Vec<Coin>,
+    pub tokens: Vec<Cw20CoinVerified>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PendingWithdrawal {
+    pub recipient: Addr,
+    pub funds: Option<Vec<Coin>>,
+    pub unlock: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Campaign {
+    pub target: Coin,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub kind: CampaignKind,
+    pub raised: Uint128,
+    pub refunded: Uint128,
+}
+
+/// A donation credited to a campaign, `counter` is the counter value it was counted at
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CampaignDonation {
+    pub counter: u64,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RewardConfig {
+    pub rates: Vec<RewardRate>,
+    pub donor_cap: Option<Uint128>,
+    pub total_cap: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Proposal {
+    pub proposer: Addr,
+    pub description: String,
+    pub action: ProposalAction,
+    pub start_height: u64,
+    pub expires: Timestamp,
+    pub total_weight: Uint128,
+    pub tally: Tally,
+    /// Only `Open`, `Rejected` once closed, or `Executed`; passing is decided on the tally
+    pub status: ProposalStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Ballot {
+    pub vote: VoteOption,
+    pub weight: Uint128,
 }
 
 pub const STATE: Item<State> = Item::new("state");
-pub const PARENT_DONATION: Item<ParentDonation> = Item::new("parent_donation");
+pub const PARENTS: Map<&Addr, ParentDonation> = Map::new("parents");
+pub const DONORS: Map<&Addr, DonorRecord> = Map::new("donors");
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+pub const FORWARDS_IN_FLIGHT: Map<u64, ParentForward> = Map::new("forwards_in_flight");
+pub const PENDING_FORWARDS: Map<&Addr, PendingForward> = Map::new("pending_forwards");
+pub const ACCEPTED_TOKENS: Map<&Addr, Uint128> = Map::new("accepted_tokens");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const GUARDIAN: Item<Addr> = Item::new("guardian");
+pub const HOOKS: Map<&Addr, HookErrorPolicy> = Map::new("hooks");
+pub const HOOKS_IN_FLIGHT: Map<u64, Addr> = Map::new("hooks_in_flight");
+pub const BADGE_COLLECTION: Item<Addr> = Item::new("badge_collection");
+pub const MILESTONES: Item<Vec<Milestone>> = Item::new("milestones");
+/// Token ids of the badges minted to a donor, keyed by the milestone id
+pub const BADGES: Map<(&Addr, &str), String> = Map::new("badges");
+/// The largest donors of each denom with their cumulative donations, from the largest
+pub const TOP_DONORS: Map<&str, Vec<(Addr, Uint128)>> = Map::new("top_donors");
+pub const REWARD_CONFIG: Item<RewardConfig> = Item::new("reward_config");
+pub const REWARD_TOKEN: Item<Addr> = Item::new("reward_token");
+pub const REWARDS_MINTED: Map<&Addr, Uint128> = Map::new("rewards_minted");
+pub const TOTAL_REWARDS_MINTED: Item<Uint128> = Item::new("total_rewards_minted");
+pub const GOVERNANCE: Item<GovernanceConfig> = Item::new("governance");
+pub const NEXT_PROPOSAL_ID: Item<u64> = Item::new("next_proposal_id");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+pub const STAKING: Item<StakingConfig> = Item::new("staking");
+pub const MAX_PARENT_DEPTH: Item<u32> = Item::new("max_parent_depth");
+pub const WITHDRAW_TIMELOCK: Item<u64> = Item::new("withdraw_timelock");
+pub const NEXT_WITHDRAWAL_ID: Item<u64> = Item::new("next_withdrawal_id");
+pub const PENDING_WITHDRAWALS: Map<u64, PendingWithdrawal> = Map::new("pending_withdrawals");
+pub const NEXT_CAMPAIGN_ID: Item<u64> = Item::new("next_campaign_id");
+pub const CAMPAIGNS: Map<u64, Campaign> = Map::new("campaigns");
+pub const CAMPAIGN_DONORS: Map<(u64, &Addr), Vec<CampaignDonation>> = Map::new("campaign_donors");
+
+/// Mirrors `State::counter` with its history, for the queries at past heights
+pub const COUNTER_HISTORY: SnapshotItem<u64> = SnapshotItem::new(
+    "counter_history",
+    "counter_history__checkpoints",
+    "counter_history__changelog",
+    Strategy::EveryBlock,
+);
+/// Counted donations per denom, with cw20 tokens keyed by their address
+pub const DONATION_TOTALS: SnapshotMap<&str, Uint128> = SnapshotMap::new(
+    "donation_totals",
+    "donation_totals__checkpoints",
+    "donation_totals__changelog",
+    Strategy::EveryBlock,
+);
+/// Counted donations of the governance denom per donor, which are their voting weights
+pub const DONOR_WEIGHTS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "donor_weights",
+    "donor_weights__checkpoints",
+    "donor_weights__changelog",
+    Strategy::EveryBlock,
+);
@@ -0,0 +1,11 @@
+use cosmwasm_schema::write_api;
+
+use factory_contract::msg::{ExecMsg, InstantiateMsg, QueryMsg};
+
+fn main() {
+    write_api! {
+        instantiate: InstantiateMsg,
+        execute: ExecMsg,
+        query: QueryMsg,
+    }
+}
@@ -0,0 +1,230 @@
+use cosmwasm_std::{Addr, DepsMut, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+
+use crate::error::ContractError;
+use crate::msg::InstantiateMsg;
+use crate::state::{CONFIG, Config};
+
+const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub const CREATE_COUNTER_REPLY: u64 = 1;
+
+pub fn instantiate(deps: DepsMut, msg: InstantiateMsg, info: MessageInfo) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner: info.sender,
+            counting_code_id: msg.counting_code_id,
+        },
+    )?;
+
+    Ok(Response::new())
+}
+
+fn ensure_owner(config: &Config, sender: &Addr) -> Result<(), ContractError> {
+    if *sender != config.owner {
+        return Err(ContractError::Unauthorized {
+            owner: config.owner.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub mod query {
+    use cosmwasm_std::{Addr, Deps, Order, StdResult};
+    use cw_storage_plus::Bound;
+
+    use crate::msg::{ChildResp, ChildrenResp, ConfigResp};
+    use crate::state::{Child, CHILDREN, CHILDREN_BY_PARENT, CONFIG};
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    pub fn config(deps: Deps) -> StdResult<ConfigResp> {
+        let config = CONFIG.load(deps.storage)?;
+
+        Ok(ConfigResp {
+            owner: config.owner,
+            counting_code_id: config.counting_code_id,
+        })
+    }
+
+    pub fn child(deps: Deps, address: String) -> StdResult<ChildResp> {
+        let address = deps.api.addr_validate(&address)?;
+        let child = CHILDREN.load(deps.storage, &address)?;
+        Ok(child_resp(address, child))
+    }
+
+    pub fn children(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<ChildrenResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let children = CHILDREN
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(address, child)| child_resp(address, child)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(ChildrenResp { children })
+    }
+
+    pub fn children_of(
+        deps: Deps,
+        parent: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ChildrenResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let parent = deps.api.addr_validate(&parent)?;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let children = CHILDREN_BY_PARENT
+            .prefix(&parent)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|address| {
+                let address = address?;
+                let child = CHILDREN.load(deps.storage, &address)?;
+                Ok(child_resp(address, child))
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ChildrenResp { children })
+    }
+
+    fn child_resp(address: Addr, child: Child) -> ChildResp {
+        ChildResp {
+            address,
+            creator: child.creator,
+            label: child.label,
+            code_id: child.code_id,
+            parents: child.parents,
+        }
+    }
+}
+
+pub mod exec {
+    use cosmwasm_std::{Coin, DepsMut, MessageInfo, Response, SubMsg, to_binary, WasmMsg};
+    use counting_contract::msg::{InstantiateMsg as CountingInstantiateMsg, Parent};
+
+    use crate::error::ContractError;
+    use crate::state::{Child, CHILDREN, CONFIG, PENDING_CHILD};
+
+    use super::{CREATE_COUNTER_REPLY, ensure_owner};
+
+    pub fn create_counter(
+        deps: DepsMut,
+        info: MessageInfo,
+        label: String,
+        counter: Option<u64>,
+        minimal_donations: Vec<Coin>,
+        parents: Vec<Parent>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        let parent_addrs = parents
+            .iter()
+            .map(|parent| {
+                let address = deps.api.addr_validate(&parent.addr)?;
+                if !CHILDREN.has(deps.storage, &address) {
+                    return Err(ContractError::UnknownParent { address: address.into() });
+                }
+                Ok(address)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        PENDING_CHILD.save(
+            deps.storage,
+            &Child {
+                creator: info.sender.clone(),
+                label: label.clone(),
+                code_id: config.counting_code_id,
+                parents: parent_addrs,
+            },
+        )?;
+
+        let msg = WasmMsg::Instantiate {
+            admin: Some(info.sender.to_string()),
+            code_id: config.counting_code_id,
+            msg: to_binary(&CountingInstantiateMsg {
+                counter,
+                minimal_donations,
+                parents,
+                ..Default::default()
+            })?,
+            funds: vec![],
+            label: label.clone(),
+        };
+
+        let resp = Response::new()
+            .add_submessage(SubMsg::reply_on_success(msg, CREATE_COUNTER_REPLY))
+            .add_attribute("action", "create_counter")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("label", label);
+
+        Ok(resp)
+    }
+
+    pub fn update_code_id(deps: DepsMut, info: MessageInfo, code_id: u64) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        ensure_owner(&config, &info.sender)?;
+
+        config.counting_code_id = code_id;
+        CONFIG.save(deps.storage, &config)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "update_code_id")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("code_id", code_id.to_string());
+
+        Ok(resp)
+    }
+}
+
+pub mod reply {
+    use cosmwasm_std::{DepsMut, Empty, Reply, Response, to_binary, WasmMsg};
+    use counting_contract::msg::ExecMsg as CountingExecMsg;
+    use cw_utils::parse_reply_instantiate_data;
+
+    use crate::error::ContractError;
+    use crate::state::{CHILDREN, CHILDREN_BY_PARENT, PENDING_CHILD};
+
+    /// Registers the new child and hands its ownership over to the creator
+    pub fn create_counter(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+        let resp = parse_reply_instantiate_data(msg)?;
+        let address = deps.api.addr_validate(&resp.contract_address)?;
+
+        let child = PENDING_CHILD.load(deps.storage)?;
+        PENDING_CHILD.remove(deps.storage);
+
+        for parent in &child.parents {
+            CHILDREN_BY_PARENT.save(deps.storage, (parent, &address), &Empty {})?;
+        }
+        CHILDREN.save(deps.storage, &address, &child)?;
+
+        let propose_owner = WasmMsg::Execute {
+            contract_addr: address.to_string(),
+            msg: to_binary(&CountingExecMsg::ProposeOwner {
+                new_owner: child.creator.to_string(),
+                expiry: None,
+            })?,
+            funds: vec![],
+        };
+
+        let resp = Response::new()
+            .add_message(propose_owner)
+            .add_attribute("child", address.as_str())
+            .add_attribute("creator", child.creator.as_str());
+
+        Ok(resp)
+    }
+}
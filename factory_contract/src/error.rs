@@ -0,0 +1,21 @@
+use cosmwasm_std::StdError;
+use cw_utils::ParseReplyError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("Unauthorized - only {owner} can call it")]
+    Unauthorized { owner: String },
+
+    #[error("Parent {address} is not a child of this factory")]
+    UnknownParent { address: String },
+
+    #[error("Unrecognized reply id: {id}")]
+    UnrecognizedReplyId { id: u64 },
+}
@@ -0,0 +1,53 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult, to_binary};
+use crate::error::ContractError;
+
+mod contract;
+pub mod msg;
+mod state;
+mod error;
+#[cfg(any(test, feature = "tests"))]
+pub mod multitest;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(deps: DepsMut, _env: Env, info: MessageInfo, msg: msg::InstantiateMsg) -> StdResult<Response> {
+    contract::instantiate(deps, msg, info)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+    use msg::QueryMsg::*;
+    use contract::query;
+
+    match msg {
+        Config {} => to_binary(&query::config(deps)?),
+        Child { address } => to_binary(&query::child(deps, address)?),
+        Children { start_after, limit } => to_binary(&query::children(deps, start_after, limit)?),
+        ChildrenOf { parent, start_after, limit } => to_binary(&query::children_of(deps, parent, start_after, limit)?),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: msg::ExecMsg) -> Result<Response, ContractError> {
+    use contract::exec;
+    use msg::ExecMsg::*;
+
+    match msg {
+        CreateCounter { label, counter, minimal_donations, parents } => {
+            exec::create_counter(deps, info, label, counter, minimal_donations, parents)
+        }
+        UpdateCodeId { code_id } => exec::update_code_id(deps, info, code_id),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    use contract::{CREATE_COUNTER_REPLY, reply};
+
+    match msg.id {
+        CREATE_COUNTER_REPLY => reply::create_counter(deps, msg),
+        id => Err(ContractError::UnrecognizedReplyId { id }),
+    }
+}
@@ -0,0 +1,68 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Coin};
+use counting_contract::msg::Parent;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub counting_code_id: u64,
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(ConfigResp)]
+    Config {},
+    #[returns(ChildResp)]
+    Child {
+        address: String,
+    },
+    #[returns(ChildrenResp)]
+    Children {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Children donating to `parent`
+    #[returns(ChildrenResp)]
+    ChildrenOf {
+        parent: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct ConfigResp {
+    pub owner: Addr,
+    pub counting_code_id: u64,
+}
+
+#[cw_serde]
+pub struct ChildResp {
+    pub address: Addr,
+    pub creator: Addr,
+    pub label: String,
+    pub code_id: u64,
+    pub parents: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct ChildrenResp {
+    pub children: Vec<ChildResp>,
+}
+
+#[cw_serde]
+pub enum ExecMsg {
+    /// Instantiates a counting contract with the sender as its admin. The ownership is
+    /// proposed to the sender, who has to accept it on the child.
+    CreateCounter {
+        label: String,
+        counter: Option<u64>,
+        minimal_donations: Vec<Coin>,
+        /// Every parent has to be a child of this factory
+        parents: Vec<Parent>,
+    },
+    /// Owner-only; used for the children created from now on
+    UpdateCodeId {
+        code_id: u64,
+    },
+}
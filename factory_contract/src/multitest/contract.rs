@@ -0,0 +1,131 @@
+use cosmwasm_std::{Addr, Coin, StdResult};
+use counting_contract::msg::Parent;
+use cw_multi_test::{App, ContractWrapper, Executor};
+
+use crate::{execute, instantiate, query, reply};
+use crate::error::ContractError;
+use crate::msg::{ChildResp, ChildrenResp, ConfigResp, ExecMsg, InstantiateMsg, QueryMsg};
+
+pub struct FactoryContract(Addr);
+
+impl FactoryContract {
+    pub fn addr(&self) -> &Addr {
+        &self.0
+    }
+
+    pub fn store_code(app: &mut App) -> u64 {
+        let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        app.store_code(Box::new(contract))
+    }
+
+    #[track_caller]
+    pub fn instantiate(app: &mut App, code_id: u64, sender: &Addr, counting_code_id: u64) -> StdResult<Self> {
+        app.instantiate_contract(
+            code_id,
+            sender.clone(),
+            &InstantiateMsg { counting_code_id },
+            &[],
+            "Factory contract",
+            None,
+        )
+            .map(FactoryContract)
+            .map_err(|err| err.downcast().unwrap())
+    }
+
+    /// Returns the address of the created counting contract
+    #[track_caller]
+    pub fn create_counter(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        label: &str,
+        minimal_donations: Vec<Coin>,
+        parents: Vec<Parent>,
+    ) -> Result<Addr, ContractError> {
+        let resp = app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::CreateCounter {
+                label: label.to_string(),
+                counter: None,
+                minimal_donations,
+                parents,
+            },
+            &[],
+        )
+            .map_err(|err| err.downcast().unwrap())?;
+
+        let child = resp
+            .events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "child")
+            .map(|attr| Addr::unchecked(&attr.value))
+            .unwrap();
+
+        Ok(child)
+    }
+
+    #[track_caller]
+    pub fn update_code_id(&self, app: &mut App, sender: &Addr, code_id: u64) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::UpdateCodeId { code_id }, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn query_config(&self, app: &App) -> StdResult<ConfigResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Config {})
+    }
+
+    #[track_caller]
+    pub fn query_child(&self, app: &App, address: &Addr) -> StdResult<ChildResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Child {
+                address: address.to_string(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_children(
+        &self,
+        app: &App,
+        start_after: impl Into<Option<String>>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<ChildrenResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Children {
+                start_after: start_after.into(),
+                limit: limit.into(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_children_of(
+        &self,
+        app: &App,
+        parent: &Addr,
+        start_after: impl Into<Option<String>>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<ChildrenResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ChildrenOf {
+                parent: parent.to_string(),
+                start_after: start_after.into(),
+                limit: limit.into(),
+            },
+        )
+    }
+}
+
+impl From<FactoryContract> for Addr {
+    fn from(contract: FactoryContract) -> Self {
+        contract.0
+    }
+}
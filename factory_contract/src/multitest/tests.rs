@@ -0,0 +1,123 @@
+use cosmwasm_std::{Addr, coins, Decimal};
+use counting_contract::msg::{DonatingPeriodKind, Parent};
+use counting_contract::multitest::contract::CountingContract;
+use cw_multi_test::App;
+
+use crate::error::ContractError;
+use crate::msg::{ChildResp, ConfigResp};
+
+use super::contract::FactoryContract;
+
+#[test]
+fn create_counters() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::default();
+
+    let counting_id = CountingContract::store_code(&mut app);
+    let factory_id = FactoryContract::store_code(&mut app);
+
+    let factory = FactoryContract::instantiate(&mut app, factory_id, &owner, counting_id).unwrap();
+
+    let root = factory
+        .create_counter(&mut app, &alice, "Root", coins(10, "atom"), vec![])
+        .unwrap();
+
+    let parent = Parent {
+        addr: root.to_string(),
+        donating_period: 2,
+        part: Decimal::percent(10),
+        period_kind: DonatingPeriodKind::Donations,
+    };
+    let child = factory
+        .create_counter(&mut app, &bob, "Child", vec![], vec![parent.clone()])
+        .unwrap();
+
+    let err = factory
+        .create_counter(
+            &mut app,
+            &bob,
+            "Orphan",
+            vec![],
+            vec![Parent {
+                addr: bob.to_string(),
+                ..parent.clone()
+            }],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnknownParent {
+            address: bob.to_string()
+        },
+    );
+
+    let resp = factory.query_child(&app, &child).unwrap();
+    assert_eq!(
+        resp,
+        ChildResp {
+            address: child.clone(),
+            creator: bob.clone(),
+            label: "Child".to_string(),
+            code_id: counting_id,
+            parents: vec![root.clone()],
+        }
+    );
+
+    let resp = factory.query_children(&app, None, None).unwrap();
+    let children: Vec<_> = resp.children.into_iter().map(|child| child.address).collect();
+    assert_eq!(children, vec![root.clone(), child.clone()]);
+
+    let resp = factory.query_children(&app, root.to_string(), None).unwrap();
+    let children: Vec<_> = resp.children.into_iter().map(|child| child.address).collect();
+    assert_eq!(children, vec![child.clone()]);
+
+    let resp = factory.query_children_of(&app, &root, None, None).unwrap();
+    let children: Vec<_> = resp.children.into_iter().map(|child| child.address).collect();
+    assert_eq!(children, vec![child.clone()]);
+
+    // The creator takes over the ownership proposed by the factory
+    let child = CountingContract::from(child);
+    assert_eq!(child.query_parents(&app).unwrap().parents, vec![parent]);
+
+    child.accept_ownership(&mut app, &bob).unwrap();
+    assert_eq!(child.query_ownership(&app).unwrap().owner, Some(bob));
+}
+
+#[test]
+fn update_code_id() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+
+    let mut app = App::default();
+
+    let counting_id = CountingContract::store_code(&mut app);
+    let factory_id = FactoryContract::store_code(&mut app);
+
+    let factory = FactoryContract::instantiate(&mut app, factory_id, &owner, counting_id).unwrap();
+
+    let err = factory
+        .update_code_id(&mut app, &alice, factory_id)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        },
+    );
+
+    factory
+        .update_code_id(&mut app, &owner, factory_id)
+        .unwrap();
+
+    let resp = factory.query_config(&app).unwrap();
+    assert_eq!(
+        resp,
+        ConfigResp {
+            owner,
+            counting_code_id: factory_id,
+        }
+    );
+}
@@ -0,0 +1,24 @@
+use cosmwasm_std::{Addr, Empty};
+use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub owner: Addr,
+    pub counting_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Child {
+    pub creator: Addr,
+    pub label: String,
+    pub code_id: u64,
+    pub parents: Vec<Addr>,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const CHILDREN: Map<&Addr, Child> = Map::new("children");
+/// Keyed by the parent and then the child
+pub const CHILDREN_BY_PARENT: Map<(&Addr, &Addr), Empty> = Map::new("children_by_parent");
+/// The child being instantiated, until its address comes back in the reply
+pub const PENDING_CHILD: Item<Child> = Item::new("pending_child");